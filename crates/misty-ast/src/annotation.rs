@@ -0,0 +1,51 @@
+use crate::{Literal, Span};
+
+/// Annotations attach metadata to definitions and their members, like `@deprecated("use v2")`.
+///
+/// Their arguments are a generic list of literals, either positional or named, like
+/// `@rust(derive = "PartialEq")`. The validator checks the annotations it knows about, the others
+/// are passed through to the code generators handling them.
+#[derive(Clone)]
+pub struct Annotation {
+    /// The name of the annotation, without the `@`.
+    ///
+    /// Must be in snake_case.
+    pub name: String,
+
+    /// The arguments of the annotation, in declaration order.
+    pub arguments: Vec<AnnotationArgument>,
+
+    /// Location of the annotation.
+    pub span: Span,
+}
+
+impl Annotation {
+    /// Value of the argument named `key`, if any.
+    pub fn argument(&self, key: &str) -> Option<&Literal> {
+        self.arguments
+            .iter()
+            .find(|argument| argument.key.as_deref() == Some(key))
+            .map(|argument| &argument.value)
+    }
+
+    /// Values of the positional arguments, in declaration order.
+    pub fn positional(&self) -> impl Iterator<Item = &Literal> {
+        self.arguments
+            .iter()
+            .filter(|argument| argument.key.is_none())
+            .map(|argument| &argument.value)
+    }
+}
+
+/// A single argument of an [Annotation].
+#[derive(Clone)]
+pub struct AnnotationArgument {
+    /// The name of the argument, `None` for positional arguments.
+    pub key: Option<String>,
+
+    /// The value of the argument.
+    pub value: Literal,
+
+    /// Location of the argument.
+    pub span: Span,
+}
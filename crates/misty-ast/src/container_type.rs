@@ -1,5 +1,8 @@
+use std::fmt;
+use std::ops::RangeInclusive;
+
 /// Available types of containers.
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Eq)]
 pub enum ContainerType {
     /// Vectors are Rust's [Vec],
     ///
@@ -10,4 +13,51 @@ pub enum ContainerType {
     ///
     /// They are represented as nullable in JavaScript.
     Option,
+
+    /// Maps are Rust's [HashMap](std::collections::HashMap), taking a key and a value type.
+    ///
+    /// Keys must be primitives or enums. They are represented as a `Map` in JavaScript.
+    Map,
+
+    /// Sets are Rust's [HashSet](std::collections::HashSet).
+    ///
+    /// Elements must be primitives or enums. They are represented as a `Set` in JavaScript.
+    Set,
+
+    /// Fixed-size arrays are Rust's arrays, holding the given number of elements: `[T; N]`.
+    ///
+    /// They are represented as an `Array` in JavaScript.
+    Array(usize),
+
+    /// Tuples are Rust's tuples, holding from 2 up to 12 types.
+    ///
+    /// They are represented as an `Array` in JavaScript.
+    Tuple,
+}
+
+impl ContainerType {
+    /// Number of types the container can hold.
+    pub fn arity(&self) -> RangeInclusive<usize> {
+        match self {
+            ContainerType::Vec
+            | ContainerType::Option
+            | ContainerType::Set
+            | ContainerType::Array(_) => 1..=1,
+            ContainerType::Map => 2..=2,
+            ContainerType::Tuple => 2..=12,
+        }
+    }
+}
+
+impl fmt::Display for ContainerType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ContainerType::Vec => write!(f, "vec"),
+            ContainerType::Option => write!(f, "option"),
+            ContainerType::Map => write!(f, "map"),
+            ContainerType::Set => write!(f, "set"),
+            ContainerType::Array(_) => write!(f, "array"),
+            ContainerType::Tuple => write!(f, "tuple"),
+        }
+    }
 }
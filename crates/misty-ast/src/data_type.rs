@@ -1,11 +1,13 @@
 use crate::ContainerType;
+use std::fmt;
 
 /// Available types of data fields and arguments can have.
 #[derive(Clone)]
 pub enum DataType {
     /// Basic data types that every language has, or can have with some adaptations.
     ///
-    /// We support the most important Rust primitives out-of-the-box.
+    /// We support the most important Rust primitives out-of-the-box, along with the well-known
+    /// `bytes`, `timestamp`, `duration` and `uuid` types.
     Primitive(String),
 
     /// User-defined types.
@@ -14,9 +16,34 @@ pub enum DataType {
     /// must reference the entire module path for resolution. Although verbose, this is to ensure no
     /// name clashes and to make intent clear.
     ///
-    /// This also permits that
+    /// Only schemas, enums and unions can be referenced, interfaces are not data types.
     UserType(String),
 
     /// Containers are types that can hold other types.
-    Container(ContainerType, Box<DataType>),
+    ///
+    /// The held types are in declaration order, a `map` holds its key type then its value type.
+    /// The size of an `array` is part of its [ContainerType].
+    Container(ContainerType, Vec<DataType>),
+}
+
+/// Formats the type as it's written in Misty, like `map<str, array<users.User, 4>>`.
+impl fmt::Display for DataType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DataType::Primitive(name) | DataType::UserType(name) => write!(f, "{name}"),
+            DataType::Container(container_type, inner) => {
+                write!(f, "{container_type}<")?;
+                for (index, data_type) in inner.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{data_type}")?;
+                }
+                if let ContainerType::Array(size) = container_type {
+                    write!(f, ", {size}")?;
+                }
+                write!(f, ">")
+            }
+        }
+    }
 }
@@ -1,4 +1,4 @@
-use crate::{Enum, Interface, Schema};
+use crate::{Annotation, Enum, Interface, Schema, Span, Union};
 
 /// Definitions are components that compose a Misty file.
 #[derive(Clone)]
@@ -14,4 +14,49 @@ pub enum Definition {
     /// Unlike the other definitions, Misty Enum's are very similar to C and TypeScript enums in the
     /// way they are laid out, they are represented as a Rust `u16`.
     Enum(Enum),
+
+    /// Unions are tagged unions, holding a single value out of many possible types.
+    Union(Union),
+}
+
+impl Definition {
+    /// Name of the definition, regardless of its kind.
+    pub fn name(&self) -> &str {
+        match self {
+            Definition::Interface(interface) => &interface.name,
+            Definition::Schema(schema) => &schema.name,
+            Definition::Enum(misty_enum) => &misty_enum.name,
+            Definition::Union(union) => &union.name,
+        }
+    }
+
+    /// Documentation of the definition, regardless of its kind.
+    pub fn docs(&self) -> &[String] {
+        match self {
+            Definition::Interface(interface) => &interface.docs,
+            Definition::Schema(schema) => &schema.docs,
+            Definition::Enum(misty_enum) => &misty_enum.docs,
+            Definition::Union(union) => &union.docs,
+        }
+    }
+
+    /// Annotations attached to the definition, regardless of its kind.
+    pub fn annotations(&self) -> &[Annotation] {
+        match self {
+            Definition::Interface(interface) => &interface.annotations,
+            Definition::Schema(schema) => &schema.annotations,
+            Definition::Enum(misty_enum) => &misty_enum.annotations,
+            Definition::Union(union) => &union.annotations,
+        }
+    }
+
+    /// Location of the definition, regardless of its kind.
+    pub fn span(&self) -> Span {
+        match self {
+            Definition::Interface(interface) => interface.span,
+            Definition::Schema(schema) => schema.span,
+            Definition::Enum(misty_enum) => misty_enum.span,
+            Definition::Union(union) => union.span,
+        }
+    }
 }
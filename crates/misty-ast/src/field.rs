@@ -1,4 +1,4 @@
-use crate::DataType;
+use crate::{Annotation, DataType, Literal, Span};
 
 /// Fields are the building blocks of schemas.
 #[derive(Clone)]
@@ -10,4 +10,16 @@ pub struct Field {
 
     /// The type of the field.
     pub field_type: DataType,
+
+    /// The value of the field when it's missing, like `retries: u32 = 3;`.
+    pub default: Option<Literal>,
+
+    /// Documentation of the field, one entry per `///` line without the leading `///`.
+    pub docs: Vec<String>,
+
+    /// Annotations attached to the field.
+    pub annotations: Vec<Annotation>,
+
+    /// Location of the field declaration.
+    pub span: Span,
 }
@@ -1,11 +1,49 @@
-use crate::Definition;
+use crate::{Definition, Import, Span};
 
 /// Root node of a Misty source file.
 #[derive(Clone)]
 pub struct File {
     /// List of imported modules.
-    pub imports: Vec<String>,
+    pub imports: Vec<Import>,
 
     /// List of definitions in the file.
     pub definitions: Vec<Definition>,
+
+    /// Location of the whole file.
+    pub span: Span,
+}
+
+impl File {
+    /// Finds the import a type reference of this file goes through.
+    ///
+    /// `None` for module-local types, and for types of modules that are not imported.
+    pub fn import_of(&self, type_name: &str) -> Option<&Import> {
+        match type_name.rsplit_once('.') {
+            Some((qualifier, _)) => self
+                .imports
+                .iter()
+                .find(|import| import.qualifier() == Some(qualifier)),
+            None => self
+                .imports
+                .iter()
+                .find(|import| import.item(type_name).is_some()),
+        }
+    }
+
+    /// Splits a type reference of this file into the path of the module declaring it and the type
+    /// name, following aliases and selective imports.
+    ///
+    /// The `module_path` is the path of this file, declaring the module-local types. The reference
+    /// is not resolved, the returned module may not exist or not declare the type.
+    pub fn qualify<'a>(&'a self, module_path: &'a str, type_name: &'a str) -> (&'a str, &'a str) {
+        let name = type_name
+            .rsplit_once('.')
+            .map_or(type_name, |(_, name)| name);
+        match self.import_of(type_name) {
+            Some(import) => (&import.path, name),
+            None => type_name
+                .rsplit_once('.')
+                .unwrap_or((module_path, type_name)),
+        }
+    }
 }
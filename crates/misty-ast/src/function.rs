@@ -1,4 +1,4 @@
-use crate::DataType;
+use crate::{Annotation, DataType, Span};
 
 /// Functions are one of the core building blocks of Misty.
 ///
@@ -27,4 +27,13 @@ pub struct Function {
     ///
     /// The boolean indicates if this is a stream or not.
     pub output: Option<(bool, DataType)>,
+
+    /// Documentation of the function, one entry per `///` line without the leading `///`.
+    pub docs: Vec<String>,
+
+    /// Annotations attached to the function.
+    pub annotations: Vec<Annotation>,
+
+    /// Location of the function declaration.
+    pub span: Span,
 }
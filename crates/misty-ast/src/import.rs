@@ -0,0 +1,55 @@
+use crate::Span;
+
+/// Import statement of a Misty source file.
+///
+/// Imports come in three forms, telling how the types of the imported module are referenced:
+/// - `import ml.analyzer;` by their full path: `ml.analyzer.Analysis`.
+/// - `import ml.analyzer as an;` through the alias: `an.Analysis`.
+/// - `import ml.analyzer.{Analysis, Report};` by the name of the selected types: `Analysis`.
+#[derive(Clone)]
+pub struct Import {
+    /// Full path of the imported module, with every segment joined by a dot: `ml.analyzer`.
+    pub path: String,
+
+    /// Alias of the module, if imported with `as`.
+    pub alias: Option<String>,
+
+    /// Types selected from the module, empty unless imported with `{...}`.
+    pub items: Vec<ImportItem>,
+
+    /// Location of the import statement.
+    pub span: Span,
+}
+
+impl Import {
+    /// Checks if this import selects some types of the module, instead of the whole module.
+    pub fn is_selective(&self) -> bool {
+        !self.items.is_empty()
+    }
+
+    /// Prefix qualifying the types referenced through this import: the alias, or the full path.
+    ///
+    /// `None` for selective imports, whose types are referenced by their name alone.
+    pub fn qualifier(&self) -> Option<&str> {
+        match &self.alias {
+            Some(alias) => Some(alias),
+            None if self.is_selective() => None,
+            None => Some(&self.path),
+        }
+    }
+
+    /// Finds a type selected by this import.
+    pub fn item(&self, name: &str) -> Option<&ImportItem> {
+        self.items.iter().find(|item| item.name == name)
+    }
+}
+
+/// A type selected by an [Import]: `Analysis` in `import ml.analyzer.{Analysis};`.
+#[derive(Clone)]
+pub struct ImportItem {
+    /// Name of the type, in PascalCase.
+    pub name: String,
+
+    /// Location of the name.
+    pub span: Span,
+}
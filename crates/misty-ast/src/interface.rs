@@ -1,4 +1,4 @@
-use crate::Function;
+use crate::{Annotation, Function, Span};
 
 /// Interfaces are similar to Protobuf's services.
 ///
@@ -21,4 +21,13 @@ pub struct Interface {
 
     /// The functions that compose the interface.
     pub functions: Vec<Function>,
+
+    /// Documentation of the interface, one entry per `///` line without the leading `///`.
+    pub docs: Vec<String>,
+
+    /// Annotations attached to the interface.
+    pub annotations: Vec<Annotation>,
+
+    /// Location of the interface definition.
+    pub span: Span,
 }
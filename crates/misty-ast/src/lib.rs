@@ -1,20 +1,30 @@
 #[doc = include_str!("../README.md")]
+mod annotation;
 mod container_type;
 mod data_type;
 mod definition;
 mod field;
 mod file;
 mod function;
+mod import;
 mod interface;
+mod literal;
 mod mist_enum;
+mod mist_union;
 mod schema;
+mod span;
 
+pub use annotation::{Annotation, AnnotationArgument};
 pub use container_type::ContainerType;
 pub use data_type::DataType;
 pub use definition::Definition;
 pub use field::Field;
 pub use file::File;
 pub use function::Function;
+pub use import::{Import, ImportItem};
 pub use interface::Interface;
-pub use mist_enum::Enum;
+pub use literal::Literal;
+pub use mist_enum::{Enum, EnumVariant};
+pub use mist_union::{Union, UnionVariant};
 pub use schema::Schema;
+pub use span::Span;
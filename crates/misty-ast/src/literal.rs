@@ -0,0 +1,36 @@
+use std::fmt;
+
+/// Literal values, used as the default values of fields.
+#[derive(Clone)]
+pub enum Literal {
+    /// An integer, like `10` or `-3`.
+    Integer(i128),
+
+    /// A floating-point number, like `1.5`.
+    Float(f64),
+
+    /// `true` or `false`.
+    Bool(bool),
+
+    /// A string, like `"text"`, with its escape sequences already resolved.
+    Str(String),
+
+    /// The absence of a value, for `option` fields.
+    None,
+}
+
+/// Formats the literal as it's written in Misty, like `"text"` or `none`.
+impl fmt::Display for Literal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Literal::Integer(value) => write!(f, "{value}"),
+            Literal::Float(value) => write!(f, "{value:?}"),
+            Literal::Bool(value) => write!(f, "{value}"),
+            Literal::Str(value) => {
+                let escaped = value.replace('\\', "\\\\").replace('"', "\\\"");
+                write!(f, "\"{escaped}\"")
+            }
+            Literal::None => write!(f, "none"),
+        }
+    }
+}
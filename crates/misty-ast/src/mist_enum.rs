@@ -1,10 +1,12 @@
+use crate::{Annotation, Span};
+
 /// Enums are similar to Protobuf's enums.
 ///
 /// Unlike the other definitions, Misty Enum's are very similar to C and TypeScript enums in the
 /// way they are laid out, they are represented as a Rust `u16`.
 ///
-/// Also, it differs from Protobuf's enums because they don't need numbering, this is automatically
-/// inferred.
+/// Also, it differs from Protobuf's enums because they don't need numbering: like in C, a variant
+/// without an explicit value takes the value of the previous variant plus one, starting at 0.
 #[derive(Clone)]
 pub struct Enum {
     /// The name of the enum.
@@ -13,7 +15,52 @@ pub struct Enum {
     pub name: String,
 
     /// The many variants that compose the enum.
+    pub variants: Vec<EnumVariant>,
+
+    /// Documentation of the enum, one entry per `///` line without the leading `///`.
+    pub docs: Vec<String>,
+
+    /// Annotations attached to the enum.
+    pub annotations: Vec<Annotation>,
+
+    /// Location of the enum definition.
+    pub span: Span,
+}
+
+impl Enum {
+    /// The value of every variant, in declaration order.
+    ///
+    /// Variants without an explicit value take the value of the previous variant plus one, and the
+    /// first variant defaults to 0. Values are not checked here, they can exceed a `u16` or repeat.
+    pub fn values(&self) -> impl Iterator<Item = (&EnumVariant, u64)> {
+        let mut next = 0u64;
+        self.variants.iter().map(move |variant| {
+            let value = variant.value.unwrap_or(next);
+            next = value.saturating_add(1);
+            (variant, value)
+        })
+    }
+}
+
+/// A single variant of an [Enum].
+#[derive(Clone)]
+pub struct EnumVariant {
+    /// The name of the variant.
+    ///
+    /// Must be in SCREAMING_SNAKE_CASE.
+    pub name: String,
+
+    /// The value explicitly given to the variant, like `ACTIVE = 3`.
     ///
-    /// Every variant must be in SCREAMING_SNAKE_CASE.
-    pub variants: Vec<String>,
+    /// Use [Enum::values] to get the value of every variant, explicit or not.
+    pub value: Option<u64>,
+
+    /// Documentation of the variant, one entry per `///` line without the leading `///`.
+    pub docs: Vec<String>,
+
+    /// Annotations attached to the variant.
+    pub annotations: Vec<Annotation>,
+
+    /// Location of the variant declaration.
+    pub span: Span,
 }
@@ -0,0 +1,47 @@
+use crate::{Annotation, DataType, Span};
+
+/// Unions are tagged unions, also known as sum types.
+///
+/// A value of a union holds exactly one of its variants, each carrying a value of its own type.
+/// They are represented as Rust enums with data, and are the way to express "one of these schemas"
+/// without a schema made of many optional fields.
+#[derive(Clone)]
+pub struct Union {
+    /// The name of the union.
+    ///
+    /// Must be in PascalCase.
+    pub name: String,
+
+    /// The many variants that compose the union.
+    pub variants: Vec<UnionVariant>,
+
+    /// Documentation of the union, one entry per `///` line without the leading `///`.
+    pub docs: Vec<String>,
+
+    /// Annotations attached to the union.
+    pub annotations: Vec<Annotation>,
+
+    /// Location of the union definition.
+    pub span: Span,
+}
+
+/// A single variant of a [Union].
+#[derive(Clone)]
+pub struct UnionVariant {
+    /// The name of the variant.
+    ///
+    /// Must be in PascalCase.
+    pub name: String,
+
+    /// The type of the value held by the variant.
+    pub variant_type: DataType,
+
+    /// Documentation of the variant, one entry per `///` line without the leading `///`.
+    pub docs: Vec<String>,
+
+    /// Annotations attached to the variant.
+    pub annotations: Vec<Annotation>,
+
+    /// Location of the variant declaration.
+    pub span: Span,
+}
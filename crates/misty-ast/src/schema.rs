@@ -1,4 +1,4 @@
-use crate::Field;
+use crate::{Annotation, Field, Span};
 
 /// Schemas are very similar to Protobuf's messages.
 ///
@@ -15,4 +15,13 @@ pub struct Schema {
 
     /// The fields that compose the schema.
     pub fields: Vec<Field>,
+
+    /// Documentation of the schema, one entry per `///` line without the leading `///`.
+    pub docs: Vec<String>,
+
+    /// Annotations attached to the schema.
+    pub annotations: Vec<Annotation>,
+
+    /// Location of the schema definition.
+    pub span: Span,
 }
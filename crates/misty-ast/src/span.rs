@@ -0,0 +1,33 @@
+use std::fmt;
+
+/// Location of an AST node in its source file.
+///
+/// Spans are captured by the parser and carried through validation so errors can point back at
+/// the exact place a node was declared.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Span {
+    /// Byte offset where the node starts, inclusive.
+    pub start: usize,
+
+    /// Byte offset where the node ends, exclusive.
+    pub end: usize,
+
+    /// Line where the node starts, starting at 1.
+    pub line: usize,
+
+    /// Column where the node starts, starting at 1.
+    pub column: usize,
+}
+
+impl Span {
+    /// Byte range covered by this span, usable to slice the original source.
+    pub fn range(&self) -> std::ops::Range<usize> {
+        self.start..self.end
+    }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
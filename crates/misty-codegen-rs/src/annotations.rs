@@ -0,0 +1,133 @@
+use misty_ast::{Annotation, Literal};
+use proc_macro2::TokenStream;
+use quote::quote;
+use std::str::FromStr;
+
+/// What the annotations of a node mean for the generated Rust code.
+///
+/// Besides the built-in `@deprecated`, the generator handles its own `@rust(...)` annotations:
+/// - `derive = "PartialEq, Eq"` adds traits to derive, on schemas, enums and unions.
+/// - `unknown_variant = true` gives an enum an `Unknown(u16)` catch-all variant.
+///
+/// The validator of misty-parser passes `@rust` annotations through without checking them, they
+/// are checked by [check] before generating any code.
+#[derive(Default)]
+pub struct RustAnnotations {
+    /// Attributes to put on the generated item.
+    pub attributes: Vec<TokenStream>,
+
+    /// Extra traits to derive, in declaration order.
+    pub derives: Vec<TokenStream>,
+
+    /// Whether the enum gets an `Unknown(u16)` catch-all variant.
+    pub unknown_variant: bool,
+}
+
+impl RustAnnotations {
+    /// Collects the annotations of a node, which accepts the `@rust` arguments in `allowed`.
+    pub fn collect(annotations: &[Annotation], allowed: &[&str]) -> Self {
+        let mut collected = Self::default();
+
+        for annotation in annotations {
+            match annotation.name.as_str() {
+                "deprecated" => {
+                    let attribute = match annotation.positional().next() {
+                        Some(Literal::Str(reason)) => quote! { #[deprecated(note = #reason)] },
+                        _ => quote! { #[deprecated] },
+                    };
+                    collected.attributes.push(attribute);
+                }
+                "rust" => collected.collect_rust(annotation, allowed),
+                _ => (),
+            }
+        }
+
+        collected
+    }
+
+    /// Helper method to collect the arguments of a single `@rust` annotation, skipping the misused
+    /// ones reported by [check].
+    fn collect_rust(&mut self, annotation: &Annotation, allowed: &[&str]) {
+        for argument in &annotation.arguments {
+            match (argument.key.as_deref(), &argument.value) {
+                (Some(key), _) if !allowed.contains(&key) => (),
+                (Some("derive"), Literal::Str(derives)) => self.derives.extend(
+                    derives
+                        .split(',')
+                        .filter_map(|derive| TokenStream::from_str(derive.trim()).ok())
+                        .filter(|derive| !derive.is_empty()),
+                ),
+                (Some("unknown_variant"), Literal::Bool(value)) => self.unknown_variant |= *value,
+                _ => (),
+            }
+        }
+    }
+
+    /// Builds the derive attribute of an item, appending the extra derives to the `base` ones.
+    ///
+    /// Extra derives already in `base` are skipped.
+    pub fn derive_attribute(&self, base: &[TokenStream]) -> TokenStream {
+        let base_names = base.iter().map(ToString::to_string).collect::<Vec<_>>();
+        let derives = base.iter().chain(
+            self.derives
+                .iter()
+                .filter(|derive| !base_names.contains(&derive.to_string())),
+        );
+        quote! { #[derive(#(#derives),*)] }
+    }
+}
+
+/// Checks the `@rust` annotations of a node, which accepts the arguments in `allowed` and can't
+/// derive the traits in `reserved`, since the generator already implements them. Derives are
+/// matched on the last segment of their path.
+///
+/// Returns the misused annotations along with the reason they are invalid.
+pub fn check<'a>(
+    annotations: &'a [Annotation],
+    allowed: &[&str],
+    reserved: &[&str],
+) -> Vec<(&'a Annotation, String)> {
+    let mut invalid = Vec::new();
+
+    for annotation in annotations
+        .iter()
+        .filter(|annotation| annotation.name == "rust")
+    {
+        for argument in &annotation.arguments {
+            let Some(key) = argument.key.as_deref() else {
+                invalid.push((annotation, "it only takes named arguments".into()));
+                continue;
+            };
+            if !allowed.contains(&key) {
+                invalid.push((
+                    annotation,
+                    format!("`{key}` is unknown or not supported here"),
+                ));
+                continue;
+            }
+
+            match (key, &argument.value) {
+                ("derive", Literal::Str(derives)) => {
+                    for derive in derives.split(',').map(str::trim) {
+                        let name = derive.rsplit("::").next().unwrap_or_default().trim();
+                        if !TokenStream::from_str(derive).is_ok_and(|tokens| !tokens.is_empty()) {
+                            invalid.push((annotation, format!("`{derive}` is not a valid path")));
+                        } else if reserved.contains(&name) {
+                            invalid.push((
+                                annotation,
+                                format!("`{derive}` is already implemented by the generator"),
+                            ));
+                        }
+                    }
+                }
+                ("unknown_variant", Literal::Bool(_)) => (),
+                _ => invalid.push((
+                    annotation,
+                    format!("`{key}` has an invalid value `{}`", argument.value),
+                )),
+            }
+        }
+    }
+
+    invalid
+}
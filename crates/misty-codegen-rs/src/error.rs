@@ -1,3 +1,11 @@
+use misty_parser::ValidationError;
+
 /// Error type returned by the Rust code generator.
 #[derive(Debug, thiserror::Error)]
-pub enum RustCodeGeneratorError {}
+pub enum RustCodeGeneratorError {
+    #[error("Failed to write the generated code: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("The workspace can't be generated as Rust code, {} error(s) found", .0.len())]
+    Invalid(Vec<ValidationError>),
+}
@@ -1,18 +1,24 @@
+mod annotations;
 mod error;
 mod options;
 mod types;
 mod util;
+mod validator;
 
 pub use error::RustCodeGeneratorError;
-pub use options::RustCodeGeneratorOptions;
+pub use options::{CollectionKind, RustCodeGeneratorOptions};
 
+use annotations::RustAnnotations;
 use heck::{ToPascalCase, ToShoutySnakeCase};
-use misty_ast::{ContainerType, DataType, Definition, Interface, Schema};
+use misty_ast::{ContainerType, DataType, Definition, Interface, Schema, Union};
 use misty_core::codegen::CodeGenerator;
-use misty_parser::Workspace;
-use proc_macro2::TokenStream;
+use misty_parser::{Severity, ValidationError, Workspace};
+use proc_macro2::{Literal, TokenStream};
 use quote::{format_ident, quote};
-use std::path::Path;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use types::ModuleContext;
 
 #[derive(Default, Clone, Copy)]
 pub struct RustCodeGenerator;
@@ -21,6 +27,41 @@ impl CodeGenerator for RustCodeGenerator {
     type Error = RustCodeGeneratorError;
     type Options = RustCodeGeneratorOptions;
 
+    fn name(&self) -> &str {
+        "rust"
+    }
+
+    /// Handles the `@rust(...)` annotations, see [RustCodeGeneratorOptions] for generator-wide
+    /// settings.
+    fn annotation_namespaces(&self) -> &[&str] {
+        &["rust"]
+    }
+
+    /// Rejects enum variants colliding with the `Unknown(u16)` catch-all variant of their enum, and
+    /// warns about schemas declaring defaults without getting a [Default] implementation.
+    fn validate(&self, options: &Self::Options, workspace: &Workspace) -> Vec<ValidationError> {
+        let mut modules = workspace.package_local_modules().iter().collect::<Vec<_>>();
+        modules.sort_by_key(|(module_name, _)| *module_name);
+
+        let mut errors = Vec::new();
+        for (module_name, file) in modules {
+            let ctx = ModuleContext {
+                workspace,
+                options,
+                module_name,
+            };
+            validator::check_module(&ctx, &file.definitions, &mut errors);
+        }
+
+        errors
+    }
+
+    /// Generates one Rust file per module, laid out the same way as the module paths.
+    ///
+    /// The output directory is expected to be the `src` directory of a dedicated crate: a `lib.rs`
+    /// declaring the top-level modules is generated, since types reference each other through
+    /// absolute `crate::` paths. Modules like `ml` that only exist as a parent of other modules
+    /// are still generated so the module tree is complete.
     #[tracing::instrument(skip(self, options, workspace, output_dir))]
     fn generate(
         &self,
@@ -28,21 +69,104 @@ impl CodeGenerator for RustCodeGenerator {
         workspace: &Workspace,
         output_dir: &Path,
     ) -> Result<(), Self::Error> {
-        // Iterate over all modules available in the workspace and generate Rust code for them.
-        for (module_name, file) in workspace.package_local_modules() {
-            // Generate the Rust code for the module.
-            let tokens = self.generate_module_content(module_name, &file.definitions);
-            println!("{}", tokens);
+        // Nothing is generated from a workspace the generator can't represent.
+        let errors = self
+            .validate(options, workspace)
+            .into_iter()
+            .filter(|error| error.severity() == Severity::Error)
+            .collect::<Vec<_>>();
+        if !errors.is_empty() {
+            return Err(RustCodeGeneratorError::Invalid(errors));
         }
-        todo!()
+
+        // Build the module tree, mapping every module path to its direct children. The crate root
+        // is the empty path.
+        let mut module_tree = BTreeMap::<String, BTreeSet<String>>::new();
+        module_tree.entry(String::new()).or_default();
+        for module_name in workspace.package_local_modules().keys() {
+            let mut parent = String::new();
+            for segment in module_name.split('.') {
+                module_tree
+                    .entry(parent.clone())
+                    .or_default()
+                    .insert(segment.to_string());
+
+                if !parent.is_empty() {
+                    parent.push('.');
+                }
+                parent.push_str(segment);
+                module_tree.entry(parent.clone()).or_default();
+            }
+        }
+
+        // Generate the Rust code for every module in the tree and write it to its file.
+        for (module_name, children) in &module_tree {
+            let definitions = workspace
+                .package_local_modules()
+                .get(module_name)
+                .map(|file| file.definitions.as_slice())
+                .unwrap_or_default();
+            let ctx = ModuleContext {
+                workspace,
+                options,
+                module_name,
+            };
+            let tokens = self.generate_module_content(&ctx, definitions, children);
+
+            let module_file = module_file_path(output_dir, module_name);
+            if let Some(parent) = module_file.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            tracing::debug!(?module_name, ?module_file, "Writing generated module");
+            fs::write(module_file, tokens.to_string())?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Finds the definition a user type declared in `module_name` refers to, along with the path of the
+/// module declaring it.
+///
+/// Aliased and selectively imported types are followed to the module declaring them.
+fn find_definition<'a>(
+    ctx: &ModuleContext<'a>,
+    module_name: &'a str,
+    user_type: &'a str,
+) -> Option<(&'a str, &'a Definition)> {
+    let workspace = ctx.workspace;
+    let (module_name, type_name) = workspace
+        .module(module_name)?
+        .qualify(module_name, user_type);
+
+    workspace
+        .module(module_name)?
+        .definitions
+        .iter()
+        .find(|definition| definition.name() == type_name)
+        .map(|definition| (module_name, definition))
+}
+
+/// Path of the file containing the generated code of a module.
+///
+/// The crate root (an empty module path) is written to `lib.rs`.
+fn module_file_path(output_dir: &Path, module_name: &str) -> PathBuf {
+    if module_name.is_empty() {
+        return output_dir.join("lib.rs");
     }
+
+    let mut path = output_dir.to_path_buf();
+    path.extend(module_name.split('.'));
+    path.set_extension("rs");
+    path
 }
 
 impl RustCodeGenerator {
     fn generate_module_content(
         &self,
-        module_name: &str,
+        ctx: &ModuleContext,
         definitions: &[Definition],
+        children: &BTreeSet<String>,
     ) -> TokenStream {
         // Standard module header without `use` statements because we use absolute module paths.
         let header_attrs = quote! {
@@ -55,29 +179,38 @@ impl RustCodeGenerator {
         // Generate the code for all the definitions in the module.
         let definitions_tokens = definitions
             .iter()
-            .map(|definition| self.generate_definition(module_name, definition))
+            .map(|definition| self.generate_definition(ctx, definition))
             .collect::<Vec<_>>();
 
+        // Declare the child modules so the whole module tree is reachable from the crate root.
+        let children = children.iter().map(|child| format_ident!("{child}"));
+
         quote! {
             #header_attrs
+            #(pub mod #children;)*
             #(#definitions_tokens)*
         }
     }
 
     /// Generates Rust code for a single definition.
-    fn generate_definition(&self, module_name: &str, definition: &Definition) -> TokenStream {
+    fn generate_definition(&self, ctx: &ModuleContext, definition: &Definition) -> TokenStream {
         match definition {
-            Definition::Schema(schema) => self.generate_schema(module_name, schema),
-            Definition::Enum(misty_enum) => self.generate_enum(module_name, misty_enum),
-            Definition::Interface(interface) => self.generate_interface(module_name, interface),
+            Definition::Schema(schema) => self.generate_schema(ctx, schema),
+            Definition::Enum(misty_enum) => self.generate_enum(ctx, misty_enum),
+            Definition::Union(union) => self.generate_union(ctx, union),
+            Definition::Interface(interface) => self.generate_interface(ctx, interface),
         }
     }
 
     /// Generates Rust code for a schema.
-    fn generate_schema(&self, module_name: &str, schema: &Schema) -> TokenStream {
+    ///
+    /// Fields with a default value get a function returning it, used by serde when the field is
+    /// missing. Schemas whose fields all have a default value, or a type implementing [Default],
+    /// implement [Default] too.
+    fn generate_schema(&self, ctx: &ModuleContext, schema: &Schema) -> TokenStream {
         // Hash builder for the discriminator of this struct.
         let mut discriminator_hash = blake3::Hasher::new();
-        discriminator_hash.update(module_name.as_bytes());
+        discriminator_hash.update(ctx.module_name.as_bytes());
 
         // Build the identifier for the struct.
         let name = format_ident!("{}", schema.name);
@@ -89,7 +222,8 @@ impl RustCodeGenerator {
             .iter()
             .map(|field| {
                 let field_name = format_ident!("{}", field.name);
-                let field_type = self.generate_data_type(&field.field_type);
+                let field_type =
+                    self.generate_data_type(ctx, &field.field_type, Some(&schema.name));
                 quote! { pub #field_name: #field_type, }
             })
             .collect::<Vec<_>>();
@@ -97,6 +231,84 @@ impl RustCodeGenerator {
             discriminator_hash.update(field.to_string().as_bytes());
         });
 
+        // Collect the annotations of the struct and its fields. Annotations don't change the
+        // discriminator.
+        let annotations = RustAnnotations::collect(&schema.annotations, &["derive"]);
+        let field_attrs = schema
+            .fields
+            .iter()
+            .map(|field| {
+                let field_annotations = RustAnnotations::collect(&field.annotations, &[]);
+                let docs = util::generate_docs(&field.docs);
+                let attributes = field_annotations.attributes;
+                quote! { #docs #(#attributes)* }
+            })
+            .collect::<Vec<_>>();
+        let attributes = &annotations.attributes;
+        let derive = annotations.derive_attribute(&[
+            quote! { Debug },
+            quote! { Clone },
+            quote! { serde::Serialize },
+            quote! { serde::Deserialize },
+        ]);
+        let docs = util::generate_docs(&schema.docs);
+
+        // Build the functions returning the default values of the fields, along with the serde
+        // attributes using them. Default values don't change the discriminator.
+        let mut default_attrs = Vec::new();
+        let mut default_fns = Vec::new();
+        for field in &schema.fields {
+            let Some(default) = &field.default else {
+                default_attrs.push(quote! {});
+                continue;
+            };
+
+            let default_fn = format_ident!("default_{}", field.name);
+            let default_path = format!("{}::{default_fn}", schema.name);
+            default_attrs.push(quote! { #[serde(default = #default_path)] });
+
+            let field_type = self.generate_data_type(ctx, &field.field_type, Some(&schema.name));
+            let value = self.literal_to_rust(&field.field_type, default);
+            default_fns.push(quote! {
+                fn #default_fn() -> #field_type {
+                    #value
+                }
+            });
+        }
+        let default_fns = (!default_fns.is_empty()).then(|| {
+            quote! {
+                impl #name {
+                    #(#default_fns)*
+                }
+            }
+        });
+
+        // Implement Default when every field has a default value.
+        let mut visited = HashSet::new();
+        let default_impl = self
+            .schema_has_default(ctx, ctx.module_name, schema, &mut visited)
+            .then(|| {
+                let values = schema.fields.iter().map(|field| {
+                    let field_name = format_ident!("{}", field.name);
+                    match field.default {
+                        Some(_) => {
+                            let default_fn = format_ident!("default_{}", field.name);
+                            quote! { #field_name: Self::#default_fn(), }
+                        }
+                        None => quote! { #field_name: Default::default(), },
+                    }
+                });
+                quote! {
+                    impl Default for #name {
+                        fn default() -> Self {
+                            Self {
+                                #(#values)*
+                            }
+                        }
+                    }
+                }
+            });
+
         // Get the hash and output that as a discriminator const.
         let discriminator_ident = format_ident!("SCHEMA_{}_ID", schema.name.to_shouty_snake_case());
         let discriminator = util::generate_discriminator(discriminator_hash.finalize());
@@ -105,59 +317,309 @@ impl RustCodeGenerator {
         quote! {
             pub const #discriminator_ident: &[u8; 32] = #discriminator;
 
-            #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+            #docs
+            #(#attributes)*
+            #derive
             pub struct #name {
-                #(#fields)*
+                #(#field_attrs #default_attrs #fields)*
+            }
+
+            #default_fns
+            #default_impl
+        }
+    }
+
+    /// Converts the default value of a field to a Rust expression of the field type.
+    ///
+    /// The literal is expected to be validated against the type.
+    fn literal_to_rust(&self, data_type: &DataType, literal: &misty_ast::Literal) -> TokenStream {
+        match (data_type, literal) {
+            (_, misty_ast::Literal::None) => quote! { None },
+            (DataType::Container(ContainerType::Option, inner), literal) => {
+                let value = self.literal_to_rust(&inner[0], literal);
+                quote! { Some(#value) }
+            }
+            (DataType::Primitive(primitive), misty_ast::Literal::Integer(value))
+                if primitive == "f32" || primitive == "f64" =>
+            {
+                let value = Literal::f64_unsuffixed(*value as f64);
+                quote! { #value }
+            }
+            (_, misty_ast::Literal::Integer(value)) => {
+                let value = Literal::i128_unsuffixed(*value);
+                quote! { #value }
+            }
+            (_, misty_ast::Literal::Float(value)) => {
+                let value = Literal::f64_unsuffixed(*value);
+                quote! { #value }
             }
+            (_, misty_ast::Literal::Bool(value)) => quote! { #value },
+            (_, misty_ast::Literal::Str(value)) => quote! { String::from(#value) },
+        }
+    }
+
+    /// Checks if the generated struct of a schema implements [Default].
+    ///
+    /// This is the case when every field has a default value, or a type implementing [Default].
+    /// Schemas already being checked are assumed to implement it, they can only be reached again
+    /// through containers implementing [Default] anyway.
+    fn schema_has_default<'a>(
+        &self,
+        ctx: &ModuleContext<'a>,
+        module_name: &'a str,
+        schema: &'a Schema,
+        visited: &mut HashSet<(&'a str, &'a str)>,
+    ) -> bool {
+        if !visited.insert((module_name, &schema.name)) {
+            return true;
+        }
+
+        schema.fields.iter().all(|field| {
+            field.default.is_some()
+                || self.has_default(ctx, module_name, &field.field_type, visited)
+        })
+    }
+
+    /// Checks if the Rust representation of a data type, declared in `module_name`, implements
+    /// [Default].
+    fn has_default<'a>(
+        &self,
+        ctx: &ModuleContext<'a>,
+        module_name: &'a str,
+        data_type: &'a DataType,
+        visited: &mut HashSet<(&'a str, &'a str)>,
+    ) -> bool {
+        match data_type {
+            // SystemTime has no meaningful default.
+            DataType::Primitive(primitive) => primitive != "timestamp",
+            DataType::Container(ContainerType::Array(_) | ContainerType::Tuple, inner) => inner
+                .iter()
+                .all(|data_type| self.has_default(ctx, module_name, data_type, visited)),
+            DataType::Container(..) => true,
+            // Enums and unions have no variant standing out as a default.
+            DataType::UserType(user_type) => match find_definition(ctx, module_name, user_type) {
+                Some((module_name, Definition::Schema(schema))) => {
+                    self.schema_has_default(ctx, module_name, schema, visited)
+                }
+                _ => false,
+            },
         }
     }
 
     /// Generates Rust code for an enum.
-    fn generate_enum(&self, module_name: &str, misty_enum: &misty_ast::Enum) -> TokenStream {
+    ///
+    /// Enums are `#[repr(u16)]` and (de)serialized as the value of their variants, so the wire form
+    /// is compact and does not depend on the order or the names of the variants.
+    ///
+    /// With [RustCodeGeneratorOptions::unknown_enum_variants], or when annotated with
+    /// `@rust(unknown_variant = true)`, enums get an `Unknown(u16)` catch-all variant instead of a
+    /// representation, holding the values they don't know about.
+    fn generate_enum(&self, ctx: &ModuleContext, misty_enum: &misty_ast::Enum) -> TokenStream {
         // Hash builder for the discriminator of this enum.
         let mut discriminator_hash = blake3::Hasher::new();
-        discriminator_hash.update(module_name.as_bytes());
+        discriminator_hash.update(ctx.module_name.as_bytes());
 
         // Build the identifier for the enum.
         let name = format_ident!("{}", misty_enum.name);
         discriminator_hash.update(name.to_string().as_bytes());
 
-        // Get the idents for all variants of the enum.
-        let variants = misty_enum
+        // Get the idents and values for all variants of the enum. Values are checked to fit in a
+        // u16 by the validator. Only explicit values are hashed, so enums without any keep the
+        // discriminator they had before values could be given.
+        let (variants, values): (Vec<_>, Vec<_>) = misty_enum
+            .values()
+            .map(|(variant, value)| {
+                let ident = format_ident!("{}", variant.name.to_pascal_case());
+                match variant.value {
+                    Some(value) => {
+                        discriminator_hash.update(format!("{ident} = {value}").as_bytes())
+                    }
+                    None => discriminator_hash.update(ident.to_string().as_bytes()),
+                };
+                (ident, Literal::u16_unsuffixed(value as u16))
+            })
+            .unzip();
+
+        // Collect the annotations of the enum and its variants. Annotations don't change the
+        // discriminator.
+        let annotations =
+            RustAnnotations::collect(&misty_enum.annotations, &["derive", "unknown_variant"]);
+        let variant_attrs = misty_enum
+            .values()
+            .map(|(variant, _)| {
+                let variant_annotations = RustAnnotations::collect(&variant.annotations, &[]);
+                let docs = util::generate_docs(&variant.docs);
+                let attributes = variant_annotations.attributes;
+                quote! { #docs #(#attributes)* }
+            })
+            .collect::<Vec<_>>();
+        let attributes = &annotations.attributes;
+        let derive = annotations.derive_attribute(&[
+            quote! { Debug },
+            quote! { Clone },
+            quote! { Copy },
+            quote! { PartialEq },
+            quote! { Eq },
+            quote! { PartialOrd },
+            quote! { Ord },
+            quote! { Hash },
+        ]);
+        let docs = util::generate_docs(&misty_enum.docs);
+
+        // Enums tolerating unknown values hold them in a catch-all variant, which cannot be given a
+        // value, so the values of the variants are only used for (de)serialization. Otherwise,
+        // unknown values are rejected.
+        let unknown_variant = ctx.options.unknown_enum_variants || annotations.unknown_variant;
+        let (enum_definition, unknown_arm, fallback_arm) = if unknown_variant {
+            (
+                quote! {
+                    pub enum #name {
+                        #(#variant_attrs #variants,)*
+                        Unknown(u16),
+                    }
+                },
+                quote! { Self::Unknown(value) => value, },
+                quote! { value => Ok(Self::Unknown(value)), },
+            )
+        } else {
+            // Empty enums cannot have a representation.
+            let repr = (!variants.is_empty()).then(|| quote! { #[repr(u16)] });
+
+            (
+                quote! {
+                    #repr
+                    pub enum #name {
+                        #(#variant_attrs #variants = #values),*
+                    }
+                },
+                quote! {},
+                quote! {
+                    value => Err(serde::de::Error::invalid_value(
+                        serde::de::Unexpected::Unsigned(value as u64),
+                        &concat!("a variant of ", stringify!(#name)),
+                    )),
+                },
+            )
+        };
+
+        // Get the hash and output that as a discriminator const.
+        let discriminator_ident =
+            format_ident!("ENUM_{}_ID", misty_enum.name.to_shouty_snake_case());
+        let discriminator = util::generate_discriminator(discriminator_hash.finalize());
+
+        // Build the final enum definition.
+        quote! {
+            pub const #discriminator_ident: &[u8; 32] = #discriminator;
+
+            #docs
+            #(#attributes)*
+            #derive
+            #enum_definition
+
+            impl serde::Serialize for #name {
+                fn serialize<S: serde::Serializer>(
+                    &self,
+                    serializer: S,
+                ) -> Result<S::Ok, S::Error> {
+                    let value: u16 = match *self {
+                        #(Self::#variants => #values,)*
+                        #unknown_arm
+                    };
+                    serializer.serialize_u16(value)
+                }
+            }
+
+            impl<'de> serde::Deserialize<'de> for #name {
+                fn deserialize<D: serde::Deserializer<'de>>(
+                    deserializer: D,
+                ) -> Result<Self, D::Error> {
+                    match <u16 as serde::Deserialize>::deserialize(deserializer)? {
+                        #(#values => Ok(Self::#variants),)*
+                        #fallback_arm
+                    }
+                }
+            }
+        }
+    }
+
+    /// Generates Rust code for a union.
+    ///
+    /// Unions are adjacently tagged, so every variant is serialized as its name along with its
+    /// value, whatever the type of the value is.
+    fn generate_union(&self, ctx: &ModuleContext, union: &Union) -> TokenStream {
+        // Hash builder for the discriminator of this union.
+        let mut discriminator_hash = blake3::Hasher::new();
+        discriminator_hash.update(ctx.module_name.as_bytes());
+
+        // Build the identifier for the union.
+        let name = format_ident!("{}", union.name);
+        discriminator_hash.update(name.to_string().as_bytes());
+
+        // Build a list of all variants of the union, each holding the value of its type.
+        let variants = union
             .variants
             .iter()
-            .map(|variant| format_ident!("{}", variant.to_pascal_case()))
+            .map(|variant| {
+                let variant_name = format_ident!("{}", variant.name);
+                let variant_type =
+                    self.generate_data_type(ctx, &variant.variant_type, Some(&union.name));
+                quote! { #variant_name(#variant_type), }
+            })
             .collect::<Vec<_>>();
         variants.iter().for_each(|variant| {
             discriminator_hash.update(variant.to_string().as_bytes());
         });
 
+        // Collect the annotations of the union and its variants. Annotations don't change the
+        // discriminator.
+        let annotations = RustAnnotations::collect(&union.annotations, &["derive"]);
+        let variant_attrs = union
+            .variants
+            .iter()
+            .map(|variant| {
+                let variant_annotations = RustAnnotations::collect(&variant.annotations, &[]);
+                let docs = util::generate_docs(&variant.docs);
+                let attributes = variant_annotations.attributes;
+                quote! { #docs #(#attributes)* }
+            })
+            .collect::<Vec<_>>();
+        let attributes = &annotations.attributes;
+        let derive = annotations.derive_attribute(&[
+            quote! { Debug },
+            quote! { Clone },
+            quote! { serde::Serialize },
+            quote! { serde::Deserialize },
+        ]);
+        let docs = util::generate_docs(&union.docs);
+
         // Get the hash and output that as a discriminator const.
-        let discriminator_ident =
-            format_ident!("ENUM_{}_ID", misty_enum.name.to_shouty_snake_case());
+        let discriminator_ident = format_ident!("UNION_{}_ID", union.name.to_shouty_snake_case());
         let discriminator = util::generate_discriminator(discriminator_hash.finalize());
 
-        // Build the final enum definition.
+        // Build the final definition, a Rust enum with a single value per variant.
         quote! {
             pub const #discriminator_ident: &[u8; 32] = #discriminator;
 
-            #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize, serde::Deserialize)]
+            #docs
+            #(#attributes)*
+            #derive
+            #[serde(tag = "type", content = "value")]
             pub enum #name {
-                #(#variants),*
+                #(#variant_attrs #variants)*
             }
         }
     }
 
     /// Generates Rust code for an interface.
-    fn generate_interface(&self, module_name: &str, interface: &Interface) -> TokenStream {
+    fn generate_interface(&self, ctx: &ModuleContext, interface: &Interface) -> TokenStream {
         // Generate the interface trait definition.
-        let interface_trait = self.generate_interface_trait(module_name, interface);
+        let interface_trait = self.generate_interface_trait(ctx, interface);
 
         // Generate a MistyService implementation for the interface.
-        let mist_service_impl = self.generate_service_impl(module_name, interface);
+        let mist_service_impl = self.generate_service_impl(ctx, interface);
 
         // Generate a MistClient implementation for the interface.
-        let mist_client_impl = self.generate_client_impl(module_name, interface);
+        let mist_client_impl = self.generate_client_impl(ctx, interface);
 
         quote! {
             #interface_trait
@@ -167,36 +629,107 @@ impl RustCodeGenerator {
     }
 
     /// Generates Rust code for the trait that defines the interface.
-    fn generate_interface_trait(&self, module_name: &str, interface: &Interface) -> TokenStream {
+    fn generate_interface_trait(
+        &self,
+        _ctx: &ModuleContext,
+        _interface: &Interface,
+    ) -> TokenStream {
         quote! {}
     }
 
     /// Generates Rust code that implements the [MistyService] trait for the interface.
-    fn generate_service_impl(&self, module_name: &str, interface: &Interface) -> TokenStream {
+    fn generate_service_impl(&self, _ctx: &ModuleContext, _interface: &Interface) -> TokenStream {
         quote! {}
     }
 
     /// Generates Rust code that creates a client for the interface.
-    fn generate_client_impl(&self, module_name: &str, interface: &Interface) -> TokenStream {
+    fn generate_client_impl(&self, _ctx: &ModuleContext, _interface: &Interface) -> TokenStream {
         quote! {}
     }
 
     /// Converts a Misty data type to its Rust representation.
-    fn generate_data_type(&self, data_type: &DataType) -> TokenStream {
+    ///
+    /// When the data type is the type of a field or a union variant, `owner` is the name of the
+    /// schema or union declaring it. It's used to store the references leading back to the owner in
+    /// a [Box], which would otherwise have an infinite size.
+    fn generate_data_type(
+        &self,
+        ctx: &ModuleContext,
+        data_type: &DataType,
+        owner: Option<&str>,
+    ) -> TokenStream {
         match data_type {
             DataType::Primitive(primitive) => self.primitive_to_rust(primitive),
-            DataType::UserType(user_type) => self.user_type_to_rust(user_type),
+            DataType::UserType(user_type) => self.user_type_to_rust(ctx, user_type),
             DataType::Container(container_type, inner) => {
-                let inner_tokens = self.generate_data_type(inner);
+                let inner_tokens = inner
+                    .iter()
+                    .map(|data_type| self.generate_data_type(ctx, data_type, owner))
+                    .collect::<Vec<_>>();
                 match container_type {
-                    ContainerType::Vec => quote! { Vec<#inner_tokens> },
-                    ContainerType::Option => quote! { Option<#inner_tokens> },
+                    ContainerType::Vec => quote! { Vec<#(#inner_tokens),*> },
+                    ContainerType::Option if self.is_recursive(ctx, owner, &inner[0]) => {
+                        quote! { Option<Box<#(#inner_tokens),*>> }
+                    }
+                    ContainerType::Option => quote! { Option<#(#inner_tokens),*> },
+                    ContainerType::Map => match ctx.options.collections {
+                        CollectionKind::Hash => {
+                            quote! { std::collections::HashMap<#(#inner_tokens),*> }
+                        }
+                        CollectionKind::BTree => {
+                            quote! { std::collections::BTreeMap<#(#inner_tokens),*> }
+                        }
+                    },
+                    ContainerType::Set => match ctx.options.collections {
+                        CollectionKind::Hash => {
+                            quote! { std::collections::HashSet<#(#inner_tokens),*> }
+                        }
+                        CollectionKind::BTree => {
+                            quote! { std::collections::BTreeSet<#(#inner_tokens),*> }
+                        }
+                    },
+                    ContainerType::Array(size) => {
+                        let size = Literal::usize_unsuffixed(*size);
+                        quote! { [#(#inner_tokens),*; #size] }
+                    }
+                    ContainerType::Tuple => quote! { (#(#inner_tokens),*) },
                 }
             }
         }
     }
 
+    /// Checks if a data type held by a field or variant of the `owner` schema or union leads back
+    /// to it.
+    ///
+    /// Arrays and tuples store their elements inline, so they lead back to the schema whenever
+    /// one of their elements does.
+    fn is_recursive(&self, ctx: &ModuleContext, owner: Option<&str>, data_type: &DataType) -> bool {
+        match (owner, data_type) {
+            (Some(owner), DataType::UserType(user_type)) => {
+                ctx.workspace
+                    .is_recursive_reference(ctx.module_name, owner, user_type)
+            }
+            (
+                Some(_),
+                DataType::Container(ContainerType::Array(_) | ContainerType::Tuple, inner),
+            ) => inner
+                .iter()
+                .any(|data_type| self.is_recursive(ctx, owner, data_type)),
+            _ => false,
+        }
+    }
+
     /// Converts a primitive type to its Rust representation.
+    ///
+    /// Well-known types rely on their serde implementation, which gives them a stable MessagePack
+    /// encoding:
+    /// - `bytes` is a `bytes::Bytes`, encoded as `bin` instead of an array of integers.
+    /// - `timestamp` is a [SystemTime](std::time::SystemTime), encoded as the seconds and
+    ///   nanoseconds elapsed since the Unix epoch.
+    /// - `duration` is a [Duration](std::time::Duration), encoded as seconds and nanoseconds.
+    /// - `uuid` is a `uuid::Uuid`, encoded as 16 bytes of `bin`.
+    ///
+    /// The generated crate must depend on `bytes` and `uuid` with their `serde` feature enabled.
     fn primitive_to_rust(&self, primitive: &str) -> TokenStream {
         match primitive {
             "u8" => quote! { u8 },
@@ -213,59 +746,386 @@ impl RustCodeGenerator {
             "f64" => quote! { f64 },
             "bool" => quote! { bool },
             "str" => quote! { String },
+            "bytes" => quote! { bytes::Bytes },
+            "timestamp" => quote! { std::time::SystemTime },
+            "duration" => quote! { std::time::Duration },
+            "uuid" => quote! { uuid::Uuid },
             _ => quote! { compile_error!("Unknown primitive") },
         }
     }
 
     /// Converts a user-defined type to its Rust representation.
-    fn user_type_to_rust(&self, user_type: &str) -> TokenStream {
-        // If the type contains a dot, then it is either `package-local` or `package-remote`.
-        // Otherwise, it is `module-local`.
-        if user_type.contains('.') {
-            // Extract each segment of the type.
-            let parts = user_type.split('.').collect::<Vec<_>>();
-
-            // Build the module definition. We currently only work with `package-local` and
-            // `module-local` types. So using `crate` is fine.
-            let mut module_segments = vec![quote! { crate }];
-            for part in parts {
-                let ident = format_ident!("{part}");
-                module_segments.push(quote! { #ident });
-            }
+    ///
+    /// Types are referenced by the path of the module declaring them, so aliases and selective
+    /// imports don't need any `use` statement.
+    fn user_type_to_rust(&self, ctx: &ModuleContext, user_type: &str) -> TokenStream {
+        let (module_name, type_name) = ctx
+            .workspace
+            .package_local_modules()
+            .get(ctx.module_name)
+            .map(|file| file.qualify(ctx.module_name, user_type))
+            .unwrap_or((ctx.module_name, user_type));
+        let ident = format_ident!("{type_name}");
+
+        // If the type is `module-local`, then we can just use the identifier directly.
+        if module_name == ctx.module_name {
+            return quote! { #ident };
+        }
+
+        let module_segments = module_name
+            .split('.')
+            .map(|segment| format_ident!("{segment}"));
 
-            // Join everything together and return the final type.
-            quote! { #(#module_segments)::* }
+        // A `package-local` type is in this crate, while a `package-remote` type is in the crate
+        // generated for its dependency, named after the dependency.
+        if ctx
+            .workspace
+            .package_local_modules()
+            .contains_key(module_name)
+        {
+            quote! { crate #(:: #module_segments)* :: #ident }
         } else {
-            // If the type is `module-local`, then we can just use the identifier directly.
-            let ident = format_ident!("{user_type}");
-            quote! { #ident }
+            quote! { #(:: #module_segments)* :: #ident }
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{RustCodeGenerator, RustCodeGeneratorOptions};
+    use crate::{RustCodeGenerator, RustCodeGeneratorError, RustCodeGeneratorOptions};
     use misty_core::codegen::CodeGenerator;
+    use misty_parser::{Severity, ValidationError, ValidationErrorKind, Workspace};
+    use std::collections::BTreeMap;
+    use std::fs;
+    use std::path::Path;
+
+    /// Helper function to generate the code of a workspace in a temporary directory, returning the
+    /// source of every file generated by path, relative to the output directory.
+    fn generate(
+        workspace: &Workspace,
+        options: &RustCodeGeneratorOptions,
+    ) -> BTreeMap<String, String> {
+        let output_dir = tempfile::tempdir().unwrap();
+        RustCodeGenerator
+            .generate(options, workspace, output_dir.path())
+            .unwrap();
+
+        let mut files = BTreeMap::new();
+        read_files(output_dir.path(), output_dir.path(), &mut files);
+        files
+    }
+
+    /// Helper function to read every file of a directory, recursively.
+    fn read_files(root: &Path, dir: &Path, files: &mut BTreeMap<String, String>) {
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                read_files(root, &path, files);
+            } else {
+                let relative = path.strip_prefix(root).unwrap();
+                let name = relative.to_string_lossy().replace('\\', "/");
+                files.insert(name, fs::read_to_string(&path).unwrap());
+            }
+        }
+    }
 
     #[test]
     fn it_works() {
         let source = r#"
-        schema MySchema { name: str; age: u8; }
-        enum MyEnum { VARIANT_A, VARIANT_B }
+        schema MySchema { name: str; age: u8; next: option<MySchema>; tags: map<str, MyEnum>;
+                          digest: array<u8, 32>; labels: set<str>; pair: tuple<str, MyEnum>;
+                          blob: bytes; at: timestamp; ttl: duration; id: uuid; }
+        enum MyEnum { VARIANT_A, VARIANT_B = 4, VARIANT_C }
+        union MyUnion { Schema: MySchema; Name: str; Next: option<MyUnion>; }
         "#;
         let source_ast = misty_parser::parse(source).unwrap();
 
-        let mut workspace = misty_parser::Workspace::new();
+        let mut workspace = Workspace::new();
         workspace.add_local_module("main", source_ast);
         workspace.validate().unwrap();
 
-        let generator = RustCodeGenerator;
-        let options = RustCodeGeneratorOptions::default();
-        generator
-            .generate(&options, &workspace, std::path::Path::new("."))
-            .unwrap();
+        let files = generate(&workspace, &RustCodeGeneratorOptions::default());
+        assert!(files["lib.rs"].contains("pub mod main ;"));
+
+        let module = &files["main.rs"];
+        assert!(module.contains("pub struct MySchema"));
+        assert!(module.contains("pub name : String"));
+        assert!(module.contains("pub next : Option < Box < MySchema >>"));
+        assert!(module.contains("pub tags : std :: collections :: HashMap < String , MyEnum >"));
+        assert!(module.contains("pub digest : [u8 ; 32]"));
+        assert!(module.contains("pub labels : std :: collections :: HashSet < String >"));
+        assert!(module.contains("pub pair : (String , MyEnum)"));
+        assert!(module.contains("pub blob : bytes :: Bytes"));
+        assert!(module.contains("pub at : std :: time :: SystemTime"));
+        assert!(module.contains("pub ttl : std :: time :: Duration"));
+        assert!(module.contains("pub id : uuid :: Uuid"));
+        assert!(module.contains(
+            "# [repr (u16)] pub enum MyEnum { VariantA = 0 , VariantB = 4 , VariantC = 5 }"
+        ));
+        assert!(module.contains("4 => Ok (Self :: VariantB) ,"));
+        assert!(module.contains("pub const UNION_MY_UNION_ID : & [u8 ; 32]"));
+        assert!(module.contains("# [serde (tag = \"type\" , content = \"value\")]"));
+        assert!(module.contains(
+            "pub enum MyUnion { Schema (MySchema) , Name (String) , \
+             Next (Option < Box < MyUnion >>) , }"
+        ));
+    }
+
+    #[test]
+    fn it_generates_the_module_tree() {
+        let mut workspace = Workspace::new();
+        let users = misty_parser::parse("schema User { id: u64; }").unwrap();
+        workspace.add_local_module("ml.users", users);
+        let jobs = misty_parser::parse("schema Job { id: u64; }").unwrap();
+        workspace.add_local_module("jobs", jobs);
+        workspace.validate().unwrap();
+
+        let files = generate(&workspace, &RustCodeGeneratorOptions::default());
+        assert_eq!(
+            files.keys().collect::<Vec<_>>(),
+            ["jobs.rs", "lib.rs", "ml.rs", "ml/users.rs"]
+        );
+        assert!(files["lib.rs"].contains("pub mod jobs ; pub mod ml ;"));
+
+        // `ml` only exists as the parent of `ml.users`, but is still generated.
+        assert!(files["ml.rs"].contains("pub mod users ;"));
+        assert!(files["ml/users.rs"].contains("pub struct User"));
+    }
+
+    #[test]
+    fn it_generates_enum_discriminators() {
+        let source = "enum Status { ACTIVE, INACTIVE }\nenum Level { LOW = 1, HIGH }";
+        let mut workspace = Workspace::new();
+        workspace.add_local_module("jobs", misty_parser::parse(source).unwrap());
+        workspace.validate().unwrap();
+
+        let module = &generate(&workspace, &RustCodeGeneratorOptions::default())["jobs.rs"];
+        let discriminator = |parts: &[&str]| {
+            let mut hash = blake3::Hasher::new();
+            parts.iter().for_each(|part| {
+                hash.update(part.as_bytes());
+            });
+            crate::util::generate_discriminator(hash.finalize()).to_string()
+        };
+
+        // Enums without explicit values keep the discriminator of their variant names.
+        let status = discriminator(&["jobs", "Status", "Active", "Inactive"]);
+        assert!(module.contains(&format!("ENUM_STATUS_ID : & [u8 ; 32] = {status} ;")));
+        let level = discriminator(&["jobs", "Level", "Low = 1", "High"]);
+        assert!(module.contains(&format!("ENUM_LEVEL_ID : & [u8 ; 32] = {level} ;")));
+    }
+
+    #[test]
+    fn it_generates_unknown_enum_variants() {
+        let source = "enum Status { ACTIVE = 1, INACTIVE }";
+        let mut workspace = Workspace::new();
+        workspace.add_local_module("status", misty_parser::parse(source).unwrap());
+        workspace.validate().unwrap();
+
+        let options = RustCodeGeneratorOptions {
+            unknown_enum_variants: true,
+            ..Default::default()
+        };
+        let module = &generate(&workspace, &options)["status.rs"];
+        assert!(module.contains("pub enum Status { Active , Inactive , Unknown (u16) , }"));
+        assert!(module.contains("Self :: Unknown (value) => value ,"));
+        assert!(module.contains("value => Ok (Self :: Unknown (value)) ,"));
+
+        // Variants colliding with the catch-all variant are rejected before generating anything.
+        let source = "enum Legacy { KNOWN, UNKNOWN }";
+        workspace.add_local_module("legacy", misty_parser::parse(source).unwrap());
+        workspace.validate().unwrap();
+        assert!(
+            RustCodeGenerator
+                .validate(&RustCodeGeneratorOptions::default(), &workspace)
+                .is_empty()
+        );
+        let errors = RustCodeGenerator.validate(&options, &workspace);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].kind(), ValidationErrorKind::ReservedVariant);
+        assert_eq!(errors[0].definition(), Some("Legacy"));
+        assert_eq!((errors[0].span().line, errors[0].span().column), (1, 22));
+        let output_dir = tempfile::tempdir().unwrap();
+        assert!(matches!(
+            RustCodeGenerator.generate(&options, &workspace, output_dir.path()),
+            Err(RustCodeGeneratorError::Invalid(errors)) if errors.len() == 1
+        ));
+    }
+
+    #[test]
+    fn it_generates_default_values() {
+        let source = r#"
+        schema Config { retries: u32 = 3; ratio: f64 = 1; offset: i8 = -2; verbose: bool = true;
+                        label: option<str> = none; name: str = "misty \"v2\"";
+                        limit: option<u16> = 5;
+                        tags: vec<str>; inner: Inner; }
+        schema Inner { scale: f32 = 0.5; }
+        schema Job { status: Status; retries: u8 = 1; }
+        enum Status { ACTIVE }
+        "#;
+        let mut workspace = Workspace::new();
+        workspace.add_local_module("config", misty_parser::parse(source).unwrap());
+        workspace.validate().unwrap();
+
+        let module = &generate(&workspace, &RustCodeGeneratorOptions::default())["config.rs"];
+        assert!(
+            module.contains("# [serde (default = \"Config::default_retries\")] pub retries : u32")
+        );
+        assert!(module.contains("fn default_retries () -> u32 { 3 }"));
+        assert!(module.contains("fn default_ratio () -> f64 { 1.0 }"));
+        assert!(module.contains("fn default_offset () -> i8 { - 2 }"));
+        assert!(module.contains("fn default_verbose () -> bool { true }"));
+        assert!(module.contains("fn default_label () -> Option < String > { None }"));
+        assert!(
+            module.contains(r#"fn default_name () -> String { String :: from ("misty \"v2\"") }"#)
+        );
+        assert!(module.contains("fn default_limit () -> Option < u16 > { Some (5) }"));
+        assert!(module.contains("impl Default for Config"));
+        assert!(module.contains("tags : Default :: default () , inner : Default :: default () ,"));
+        assert!(module.contains("impl Default for Inner"));
+        assert!(!module.contains("impl Default for Job"));
+
+        // Only schemas declaring defaults are expected to get an implementation.
+        let warnings = RustCodeGenerator.validate(&RustCodeGeneratorOptions::default(), &workspace);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].kind(), ValidationErrorKind::MissingDefault);
+        assert_eq!(warnings[0].definition(), Some("Job"));
+        assert_eq!(warnings[0].severity(), Severity::Warning);
+    }
+
+    #[test]
+    fn it_generates_annotations() {
+        let users = |source: &str| {
+            let mut workspace = Workspace::new();
+            for namespace in RustCodeGenerator.annotation_namespaces() {
+                workspace.add_annotation_namespace(namespace);
+            }
+            workspace.add_local_module("users", misty_parser::parse(source).unwrap());
+            workspace.validate().unwrap();
+            workspace
+        };
+        let generate_users = |source: &str| {
+            generate(&users(source), &RustCodeGeneratorOptions::default())
+                .remove("users.rs")
+                .unwrap()
+        };
+
+        let module = generate_users(
+            r#"
+            @deprecated("use UserV2") @rust(derive = "PartialEq, Clone")
+            schema User { @deprecated id: u64; name: str; }
+            @rust(unknown_variant = true) enum Role { @deprecated ADMIN, GUEST }
+            union Contact { @deprecated("no more faxes") Fax: str; }
+            "#,
+        );
+        assert!(module.contains(
+            "# [deprecated (note = \"use UserV2\")] # [derive (Debug , Clone , \
+             serde :: Serialize , serde :: Deserialize , PartialEq)] pub struct User"
+        ));
+        assert!(module.contains("# [deprecated] pub id : u64"));
+        assert!(
+            module.contains("pub enum Role { # [deprecated] Admin , Guest , Unknown (u16) , }")
+        );
+        assert!(module.contains("# [deprecated (note = \"no more faxes\")] Fax (String) ,"));
+
+        // Misused `@rust` arguments, and derives of traits the generator implements, are rejected.
+        let errors = RustCodeGenerator.validate(
+            &RustCodeGeneratorOptions::default(),
+            &users(
+                r#"
+                @rust(derive = "Default", "Hash") schema User { @rust(derive = "Hash") name: str; }
+                @rust(derive = "serde::Serialize, Copy", unknown_variant = 1) enum Role { ADMIN }
+                @rust(derive = "1 +, ") union Contact { Fax: str; }
+                "#,
+            ),
+        );
+        let reasons = errors
+            .iter()
+            .map(|error| match error {
+                ValidationError::InvalidAnnotation { reason, .. } => reason.as_str(),
+                _ => panic!("unexpected error: {error}"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            reasons,
+            vec![
+                "`Default` is already implemented by the generator",
+                "it only takes named arguments",
+                "`derive` is unknown or not supported here",
+                "`serde::Serialize` is already implemented by the generator",
+                "`unknown_variant` has an invalid value `1`",
+                "`` is not a valid path",
+            ]
+        );
+        assert_eq!((errors[0].span().line, errors[0].span().column), (2, 17));
+
+        // Annotations don't change the discriminators.
+        let plain =
+            generate_users("schema User { id: u64; name: str; } enum Role { ADMIN, GUEST }");
+        let discriminators = |module: &str| {
+            module
+                .split("pub const")
+                .skip(1)
+                .filter_map(|item| {
+                    item.split_once("] ;")
+                        .map(|(constant, _)| constant.to_string())
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(discriminators(&module)[..2], discriminators(&plain));
+    }
+
+    #[test]
+    fn it_generates_docs() {
+        let source = "/// A user.\n/// Quite simple.\nschema User { /// Unique.\n id: u64; }\n\
+                      /// A role.\nenum Role { /// Can do anything.\n ADMIN }\n\
+                      /// A contact.\nunion Contact { /// An email address.\n Email: str; }";
+        let mut workspace = Workspace::new();
+        workspace.add_local_module("users", misty_parser::parse(source).unwrap());
+        workspace.validate().unwrap();
+
+        let module = &generate(&workspace, &RustCodeGeneratorOptions::default())["users.rs"];
+        assert!(module.contains(
+            "# [doc = \"A user.\"] # [doc = \"Quite simple.\"] # [derive (Debug , Clone , \
+             serde :: Serialize , serde :: Deserialize)] pub struct User"
+        ));
+        assert!(module.contains("# [doc = \"Unique.\"] pub id : u64"));
+        assert!(module.contains("# [doc = \"A role.\"] # [derive"));
+        assert!(module.contains("# [doc = \"Can do anything.\"] Admin = 0"));
+        assert!(module.contains("# [doc = \"A contact.\"] # [derive"));
+        assert!(module.contains("# [doc = \"An email address.\"] Email (String)"));
+    }
+
+    #[test]
+    fn it_follows_aliases_and_selective_imports() {
+        let analyzer = "schema Analysis { score: f32; } enum Level { LOW }";
+        let jobs = "import ml.analyzer as an;\nimport ml.analyzer.{Level};\n\
+                    schema Job { analysis: an.Analysis; levels: vec<Level>; }";
+        let mut workspace = Workspace::new();
+        workspace.add_local_module("ml.analyzer", misty_parser::parse(analyzer).unwrap());
+        workspace.add_local_module("jobs", misty_parser::parse(jobs).unwrap());
+        workspace.validate().unwrap();
+
+        let module = &generate(&workspace, &RustCodeGeneratorOptions::default())["jobs.rs"];
+        assert!(module.contains("pub analysis : crate :: ml :: analyzer :: Analysis"));
+        assert!(module.contains("pub levels : Vec < crate :: ml :: analyzer :: Level >"));
+    }
+
+    #[test]
+    fn it_references_dependencies() {
+        let mut common_types = Workspace::new();
+        let users = misty_parser::parse("enum Role { ADMIN }").unwrap();
+        common_types.add_local_module("users", users);
+
+        let jobs = "import common_types.users.{Role};\nschema Job { role: Role; }";
+        let mut workspace = Workspace::new();
+        workspace.add_local_module("jobs", misty_parser::parse(jobs).unwrap());
+        workspace.add_dependency("common_types", common_types);
+        workspace.validate().unwrap();
 
-        assert!(false);
+        // Dependencies are generated in their own crate, and never in this one.
+        let files = generate(&workspace, &RustCodeGeneratorOptions::default());
+        assert!(files["jobs.rs"].contains("pub role : :: common_types :: users :: Role"));
+        assert!(!files.contains_key("users.rs"));
     }
 }
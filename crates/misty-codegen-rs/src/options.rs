@@ -1,3 +1,41 @@
+use serde::Deserialize;
+
 /// Options for the Rust code generator.
-#[derive(Default, Clone, Copy)]
-pub struct RustCodeGeneratorOptions {}
+///
+/// They are read from the `[generators.rust]` table of `Misty.toml`, every option being optional:
+///
+/// ```toml
+/// [generators.rust]
+/// output = "types/src"
+/// collections = "btree"
+/// unknown_enum_variants = true
+/// ```
+#[derive(Default, Clone, Copy, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RustCodeGeneratorOptions {
+    /// Kind of collection generated for maps and sets.
+    pub collections: CollectionKind,
+
+    /// Whether enums get an `Unknown(u16)` catch-all variant.
+    ///
+    /// Values unknown to the generated code are deserialized into it instead of failing the whole
+    /// message, so older clients keep working when variants are added. Enums declaring an `UNKNOWN`
+    /// variant of their own are rejected when validating the workspace for the generator.
+    ///
+    /// Single enums can opt in with the `@rust(unknown_variant = true)` annotation instead.
+    pub unknown_enum_variants: bool,
+}
+
+/// Kinds of collections available to represent Misty maps and sets.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CollectionKind {
+    /// Hash-based collections, like [HashMap](std::collections::HashMap).
+    #[default]
+    Hash,
+
+    /// Ordered collections, like [BTreeMap](std::collections::BTreeMap).
+    ///
+    /// Entries are always iterated in the same order, so serialization is deterministic.
+    BTree,
+}
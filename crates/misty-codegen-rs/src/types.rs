@@ -1 +1,14 @@
+use crate::RustCodeGeneratorOptions;
+use misty_parser::Workspace;
 
+/// Everything the generator needs to know about the module it's generating code for.
+pub struct ModuleContext<'a> {
+    /// The validated workspace being generated.
+    pub workspace: &'a Workspace,
+
+    /// Options of the current generation.
+    pub options: &'a RustCodeGeneratorOptions,
+
+    /// Path of the module being generated.
+    pub module_name: &'a str,
+}
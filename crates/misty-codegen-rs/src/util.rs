@@ -9,3 +9,10 @@ pub fn generate_discriminator(hash: Hash) -> TokenStream {
         &[#(#bytes), *]
     }
 }
+
+/// Helper function to generate the `#[doc]` attributes of an item from its doc comments.
+pub fn generate_docs(docs: &[String]) -> TokenStream {
+    quote! {
+        #(#[doc = #docs])*
+    }
+}
@@ -0,0 +1,129 @@
+use crate::RustCodeGenerator;
+use crate::annotations::{self, RustAnnotations};
+use crate::types::ModuleContext;
+use heck::ToPascalCase;
+use misty_ast::{Annotation, Definition, Enum, Schema};
+use misty_parser::ValidationError;
+use std::collections::HashSet;
+
+/// Checks the definitions of a local module against what the generator can represent.
+pub fn check_module(
+    ctx: &ModuleContext,
+    definitions: &[Definition],
+    errors: &mut Vec<ValidationError>,
+) {
+    for definition in definitions {
+        let name = definition.name();
+        let mut check = |annotations: &[Annotation], allowed: &[&str], reserved: &[&str]| {
+            check_annotations(ctx, name, annotations, allowed, reserved, errors)
+        };
+
+        // Schemas get a Default implementation, and enums manual serde implementations.
+        match definition {
+            Definition::Schema(schema) => {
+                check(&schema.annotations, &["derive"], &["Default"]);
+                for field in &schema.fields {
+                    check(&field.annotations, &[], &[]);
+                }
+                check_schema(ctx, schema, errors);
+            }
+            Definition::Enum(misty_enum) => {
+                check(
+                    &misty_enum.annotations,
+                    &["derive", "unknown_variant"],
+                    &["Serialize", "Deserialize"],
+                );
+                for variant in &misty_enum.variants {
+                    check(&variant.annotations, &[], &[]);
+                }
+                check_enum(ctx, misty_enum, errors);
+            }
+            Definition::Union(union) => {
+                check(&union.annotations, &["derive"], &[]);
+                for variant in &union.variants {
+                    check(&variant.annotations, &[], &[]);
+                }
+            }
+            // No `@rust` arguments apply to interfaces and functions yet.
+            Definition::Interface(interface) => {
+                check(&interface.annotations, &[], &[]);
+                for function in &interface.functions {
+                    check(&function.annotations, &[], &[]);
+                }
+            }
+        }
+    }
+}
+
+/// Helper function to check the `@rust` annotations of a node, see [annotations::check].
+fn check_annotations(
+    ctx: &ModuleContext,
+    definition: &str,
+    annotations: &[Annotation],
+    allowed: &[&str],
+    reserved: &[&str],
+    errors: &mut Vec<ValidationError>,
+) {
+    for (annotation, reason) in annotations::check(annotations, allowed, reserved) {
+        tracing::debug!(?definition, ?reason, "Invalid `@rust` annotation");
+        errors.push(ValidationError::InvalidAnnotation {
+            module: ctx.module_name.to_string(),
+            definition: definition.to_string(),
+            name: annotation.name.clone(),
+            reason,
+            span: annotation.span,
+        });
+    }
+}
+
+/// Helper function to warn about schemas declaring default values, but getting no [Default]
+/// implementation because one of their other fields has no default.
+fn check_schema(ctx: &ModuleContext, schema: &Schema, errors: &mut Vec<ValidationError>) {
+    if schema.fields.iter().all(|field| field.default.is_none()) {
+        return;
+    }
+
+    let missing = schema.fields.iter().find(|field| {
+        field.default.is_none()
+            && !RustCodeGenerator.has_default(
+                ctx,
+                ctx.module_name,
+                &field.field_type,
+                &mut HashSet::from([(ctx.module_name, schema.name.as_str())]),
+            )
+    });
+    if let Some(field) = missing {
+        tracing::debug!(?schema.name, ?field.name, "Schema without a Default implementation");
+        errors.push(ValidationError::MissingDefault {
+            module: ctx.module_name.to_string(),
+            definition: schema.name.clone(),
+            field: field.name.clone(),
+            type_name: field.field_type.to_string(),
+            span: field.span,
+        });
+    }
+}
+
+/// Helper function to check that no variant of an enum collides with its `Unknown(u16)` catch-all
+/// variant, when it gets one.
+fn check_enum(ctx: &ModuleContext, misty_enum: &Enum, errors: &mut Vec<ValidationError>) {
+    let annotations =
+        RustAnnotations::collect(&misty_enum.annotations, &["derive", "unknown_variant"]);
+    if !ctx.options.unknown_enum_variants && !annotations.unknown_variant {
+        return;
+    }
+
+    for variant in &misty_enum.variants {
+        if variant.name.to_pascal_case() == "Unknown" {
+            tracing::debug!(?misty_enum.name, "Enum variant collides with the catch-all variant");
+            errors.push(ValidationError::ReservedVariant {
+                module: ctx.module_name.to_string(),
+                definition: misty_enum.name.clone(),
+                name: variant.name.clone(),
+                reason: "the enum gets an `Unknown(u16)` catch-all variant for unknown values"
+                    .into(),
+                span: variant.span,
+            });
+        }
+    }
+}
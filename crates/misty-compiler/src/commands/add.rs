@@ -0,0 +1,135 @@
+use crate::CompilerError;
+use crate::project::Project;
+use crate::registry::{RegistryArgs, load_manifest};
+use clap::Args;
+use misty_core::manifest::{Dependency, Manifest};
+use misty_core::registry::{Registry, RegistryError, install_dependencies};
+use misty_parser::is_snake_case;
+use semver::{Comparator, Op, VersionReq};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::str::FromStr;
+
+#[derive(Args)]
+pub struct AddArgs {
+    /// Package to add, with an optional version requirement: `name` or `name@1.2`.
+    package: PackageSpec,
+
+    /// Path of a local package, relative to the project root, instead of a registry package.
+    #[arg(long)]
+    path: Option<PathBuf>,
+
+    /// Root directory of the project, containing its `Misty.toml`.
+    #[arg(long, default_value = ".")]
+    root: PathBuf,
+
+    #[command(flatten)]
+    registry: RegistryArgs,
+}
+
+/// A package given on the command line, as `name` or `name@requirement`.
+#[derive(Clone)]
+struct PackageSpec {
+    name: String,
+    requirement: Option<VersionReq>,
+}
+
+impl FromStr for PackageSpec {
+    type Err = String;
+
+    /// Parses a package given on the command line, checking its name before it is used to look
+    /// the package up in a registry.
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let (name, requirement) = match spec.split_once('@') {
+            Some((name, requirement)) => {
+                let requirement = requirement.parse().map_err(|error| format!("{error}"))?;
+                (name, Some(requirement))
+            }
+            None => (spec, None),
+        };
+        if !is_snake_case(name) {
+            return Err(format!("`{name}` is not a snake_case package name"));
+        }
+
+        Ok(Self {
+            name: name.to_string(),
+            requirement,
+        })
+    }
+}
+
+/// Adds a dependency to the manifest of the project, then installs the registry dependencies and
+/// pins every dependency in the lockfile.
+///
+/// Registry packages added without a requirement get the newest version published, as a caret
+/// requirement.
+pub fn run(args: AddArgs) -> Result<ExitCode, CompilerError> {
+    let manifest = load_manifest(&args.root)?;
+    let registry = args.registry.registry(&args.root, &manifest);
+    let PackageSpec { name, requirement } = args.package;
+
+    let dependency = match args.path {
+        Some(path) => Dependency {
+            version: requirement,
+            path: Some(path),
+        },
+        None => {
+            let registry = registry.as_ref().ok_or(CompilerError::MissingRegistry)?;
+            Dependency {
+                version: Some(registry_requirement(registry, &name, requirement)?),
+                path: None,
+            }
+        }
+    };
+
+    let manifest = Manifest::add_dependency(&args.root, &name, &dependency)?;
+    if let Some(registry) = &registry {
+        install_dependencies(registry, &args.root, &manifest)?;
+    }
+    Project::load(&args.root)?.lock()?;
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Helper function to check a requirement on a registry package before adding it, or to require
+/// its newest version when there is none.
+///
+/// Pre-releases are only picked by explicit requirements.
+fn registry_requirement(
+    registry: &dyn Registry,
+    name: &str,
+    requirement: Option<VersionReq>,
+) -> Result<VersionReq, CompilerError> {
+    let versions = registry.versions(name)?;
+    let no_match = |requirement: &VersionReq| RegistryError::NoMatchingVersion {
+        name: name.to_string(),
+        requirements: requirement.to_string(),
+    };
+
+    if let Some(requirement) = requirement {
+        if !versions
+            .iter()
+            .any(|entry| requirement.matches(&entry.version))
+        {
+            return Err(no_match(&requirement).into());
+        }
+        return Ok(requirement);
+    }
+
+    let newest = versions
+        .iter()
+        .map(|entry| &entry.version)
+        .filter(|version| version.pre.is_empty())
+        .max()
+        .ok_or_else(|| no_match(&VersionReq::STAR))?;
+
+    Ok(VersionReq {
+        comparators: vec![Comparator {
+            op: Op::Caret,
+            major: newest.major,
+            minor: Some(newest.minor),
+            patch: Some(newest.patch),
+            pre: newest.pre.clone(),
+        }],
+    })
+}
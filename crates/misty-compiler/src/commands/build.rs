@@ -0,0 +1,155 @@
+use crate::CompilerError;
+use crate::project::{Project, print_diagnostic};
+use clap::Args;
+use misty_codegen_rs::RustCodeGenerator;
+use misty_core::codegen::CodeGenerator;
+use misty_core::lockfile::Lockfile;
+use misty_core::manifest::GeneratorConfig;
+use misty_parser::{Diagnostic, Severity};
+use std::fs;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+#[derive(Args)]
+pub struct BuildArgs {
+    /// Root directory of the project, containing its `Misty.toml`.
+    #[arg(default_value = ".")]
+    root: PathBuf,
+
+    /// Fails instead of writing `Misty.lock` when the project has dependencies but no lockfile.
+    #[arg(long)]
+    locked: bool,
+}
+
+/// Runs every generator configured in the manifest of the project.
+///
+/// Dependencies are checked against the lockfile first, so code is only generated from the
+/// sources it pins. Projects with dependencies but no lockfile yet get one, unless `--locked` is
+/// given.
+///
+/// Dependencies are not generated: the Rust code references them as crates, which are built from
+/// the dependencies themselves, and a warning is printed when the crate of the output directory
+/// doesn't depend on them.
+pub fn run(args: BuildArgs) -> Result<ExitCode, CompilerError> {
+    let project = Project::load(&args.root)?;
+    let Some(manifest) = &project.manifest else {
+        return Err(CompilerError::MissingManifest(args.root));
+    };
+    project.print_warnings();
+
+    match Lockfile::load(&project.root)? {
+        Some(lockfile) => lockfile
+            .verify(&project.workspace)
+            .map_err(CompilerError::OutdatedLockfile)?,
+        None if project.workspace.dependencies().is_empty() => {}
+        None if args.locked => return Err(CompilerError::MissingLockfile(args.root)),
+        None => {
+            project.lock()?;
+        }
+    }
+
+    for (name, config) in &manifest.generators {
+        match name.as_str() {
+            "rust" => {
+                generate(&project, &RustCodeGenerator, config)?;
+                check_dependency_crates(&project, config);
+            }
+            _ => return Err(CompilerError::UnknownGenerator(name.clone())),
+        }
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Helper function to run a generator in its output directory, relative to the project root.
+///
+/// What the generator can't represent is printed as diagnostics, failing the build on errors.
+fn generate<G: CodeGenerator>(
+    project: &Project,
+    generator: &G,
+    config: &GeneratorConfig,
+) -> Result<(), CompilerError> {
+    let options = config.options::<G::Options>(generator.name())?;
+    let validation_errors = generator.validate(&options, &project.workspace);
+    for error in &validation_errors {
+        if let Some(file) = project.workspace.source_file(error.module()) {
+            print_diagnostic(file, &Diagnostic::from(error));
+        }
+    }
+    let errors = validation_errors
+        .iter()
+        .filter(|error| error.severity() == Severity::Error)
+        .count();
+    if errors > 0 {
+        return Err(CompilerError::InvalidProject(errors));
+    }
+
+    let output_dir = project.root.join(&config.output);
+    fs::create_dir_all(&output_dir).map_err(|source| CompilerError::Write {
+        path: output_dir.clone(),
+        source,
+    })?;
+
+    generator
+        .generate(&options, &project.workspace, &output_dir)
+        .map_err(|error| CompilerError::Generate {
+            generator: generator.name().to_string(),
+            source: Box::new(error),
+        })
+}
+
+/// Helper function to warn about the dependencies missing from the Cargo crate the Rust code is
+/// generated in.
+///
+/// Types of a dependency are referenced through a crate named after it, like `::common_types`,
+/// which is generated by building the dependency itself. The crate is the nearest `Cargo.toml`
+/// above the output directory.
+fn check_dependency_crates(project: &Project, config: &GeneratorConfig) {
+    let mut dependencies = project.workspace.dependencies().keys().collect::<Vec<_>>();
+    if dependencies.is_empty() {
+        return;
+    }
+    dependencies.sort();
+
+    let output_dir = project.root.join(&config.output);
+    let Some(cargo_manifest) = output_dir
+        .ancestors()
+        .map(|dir| dir.join("Cargo.toml"))
+        .find(|path| path.exists())
+    else {
+        eprintln!(
+            "warning: no Cargo.toml found above `{}`, the generated code needs the crates of {}",
+            output_dir.display(),
+            dependencies
+                .iter()
+                .map(|name| format!("`{name}`"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        return;
+    };
+
+    // Cargo accepts both `-` and `_` in crate names, which are referenced with `_` in Rust code.
+    let declared = fs::read_to_string(&cargo_manifest)
+        .ok()
+        .and_then(|source| source.parse::<toml::Table>().ok())
+        .and_then(|table| {
+            let dependencies = table.get("dependencies")?.as_table()?;
+            Some(
+                dependencies
+                    .keys()
+                    .map(|name| name.replace('-', "_"))
+                    .collect::<Vec<_>>(),
+            )
+        })
+        .unwrap_or_default();
+    for name in dependencies {
+        if !declared.contains(name) {
+            eprintln!(
+                "warning: the generated code references the `{name}` crate, which is not a \
+                 dependency in `{}`",
+                cargo_manifest.display()
+            );
+        }
+    }
+}
@@ -0,0 +1,20 @@
+use crate::CompilerError;
+use crate::project::Project;
+use clap::Args;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+#[derive(Args)]
+pub struct CheckArgs {
+    /// Root directory of the project.
+    #[arg(default_value = ".")]
+    root: PathBuf,
+}
+
+/// Validates the project, printing its warnings without failing on them.
+pub fn run(args: CheckArgs) -> Result<ExitCode, CompilerError> {
+    let project = Project::load(&args.root)?;
+    project.print_warnings();
+
+    Ok(ExitCode::SUCCESS)
+}
@@ -0,0 +1,36 @@
+use crate::CompilerError;
+use crate::project::Project;
+use crate::registry::{RegistryArgs, load_manifest};
+use clap::Args;
+use misty_core::registry::install_dependencies;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+#[derive(Args)]
+pub struct FetchArgs {
+    /// Root directory of the project, containing its `Misty.toml`.
+    #[arg(default_value = ".")]
+    root: PathBuf,
+
+    #[command(flatten)]
+    registry: RegistryArgs,
+}
+
+/// Installs the registry dependencies of the project in its vendor directory, at the versions
+/// pinned by the lockfile when they still match the manifest, then pins every dependency in the
+/// lockfile.
+///
+/// Without a registry, dependencies already installed or given by path are pinned as they are.
+pub fn run(args: FetchArgs) -> Result<ExitCode, CompilerError> {
+    let manifest = load_manifest(&args.root)?;
+    if let Some(registry) = args.registry.registry(&args.root, &manifest) {
+        install_dependencies(&registry, &args.root, &manifest)?;
+    }
+
+    let project = Project::load(&args.root)?;
+    for package in &project.lock()?.packages {
+        println!("Locked {} {}", package.name, package.version);
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
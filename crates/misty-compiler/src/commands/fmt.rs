@@ -0,0 +1,72 @@
+use crate::CompilerError;
+use crate::files::collect_misty_files;
+use clap::Args;
+use misty_core::formatter::format_source;
+use misty_parser::Diagnostic;
+use std::fs;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+#[derive(Args)]
+pub struct FmtArgs {
+    /// Files or directories to format, directories are searched for `.misty` files.
+    #[arg(default_value = ".")]
+    paths: Vec<PathBuf>,
+
+    /// Only check the formatting, exiting with a failure when a file is not formatted.
+    ///
+    /// No file is written in this mode.
+    #[arg(long)]
+    check: bool,
+}
+
+/// Formats every Misty file, or checks that they are formatted with `--check`.
+///
+/// Files that fail to parse are reported and left untouched, and make the command fail.
+pub fn run(args: FmtArgs) -> Result<ExitCode, CompilerError> {
+    let mut success = true;
+
+    for path in collect_misty_files(&args.paths)? {
+        let source = fs::read_to_string(&path).map_err(|source| CompilerError::Read {
+            path: path.clone(),
+            source,
+        })?;
+
+        let formatted = match format_source(&source) {
+            Ok(formatted) => formatted,
+            Err(error) => {
+                let file_name = path.display().to_string();
+                eprint!("{}", Diagnostic::from(&error).render(&file_name, &source));
+                success = false;
+                continue;
+            }
+        };
+        if formatted == source {
+            continue;
+        }
+
+        if args.check {
+            // Point at the first line that differs, like the start of a diff.
+            let line = source
+                .lines()
+                .zip(formatted.lines())
+                .position(|(original, formatted)| original != formatted)
+                .unwrap_or_else(|| source.lines().count().min(formatted.lines().count()))
+                + 1;
+            println!("Diff in {} at line {line}", path.display());
+            success = false;
+        } else {
+            fs::write(&path, formatted).map_err(|source| CompilerError::Write {
+                path: path.clone(),
+                source,
+            })?;
+            println!("Formatted {}", path.display());
+        }
+    }
+
+    Ok(if success {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
@@ -0,0 +1,96 @@
+use crate::CompilerError;
+use crate::project::Project;
+use clap::{Args, ValueEnum};
+use misty_core::lint::{LintConfig, lint_workspace};
+use std::fs;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+#[derive(Args)]
+pub struct LintArgs {
+    /// Root directory of the project.
+    #[arg(default_value = ".")]
+    root: PathBuf,
+
+    /// Configuration file enabling and disabling rules, `Misty.toml` in the root by default.
+    #[arg(long)]
+    config: Option<PathBuf>,
+
+    /// Format of the report.
+    #[arg(long, value_enum, default_value_t = OutputFormat::Human)]
+    format: OutputFormat,
+}
+
+/// Formats of the lint report.
+#[derive(Clone, Copy, ValueEnum)]
+enum OutputFormat {
+    /// One line per lint, with its location.
+    Human,
+
+    /// A JSON array of lints, for other tools.
+    Json,
+}
+
+/// Lints the project, failing when any lint is found.
+pub fn run(args: LintArgs) -> Result<ExitCode, CompilerError> {
+    let config = read_config(&args)?;
+    // Validation warnings are not printed, the `unused_import` rule reports them as lints.
+    let project = Project::load(&args.root)?;
+    let lints = lint_workspace(&project.workspace, &config);
+
+    match args.format {
+        OutputFormat::Human => {
+            for lint in &lints {
+                println!(
+                    "{}:{}: warning[{}]: {}",
+                    project.file_name(&lint.module),
+                    lint.span,
+                    lint.rule,
+                    lint.message
+                );
+            }
+        }
+        OutputFormat::Json => {
+            let report = lints
+                .iter()
+                .map(|lint| {
+                    serde_json::json!({
+                        "rule": lint.rule.name(),
+                        "module": lint.module,
+                        "file": project.file_name(&lint.module),
+                        "definition": lint.definition,
+                        "message": lint.message,
+                        "line": lint.span.line,
+                        "column": lint.span.column,
+                        "start": lint.span.start,
+                        "end": lint.span.end,
+                    })
+                })
+                .collect::<Vec<_>>();
+            println!("{}", serde_json::Value::Array(report));
+        }
+    }
+
+    Ok(if lints.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
+
+/// Reads the lint configuration, every rule is enabled without a configuration file.
+fn read_config(args: &LintArgs) -> Result<LintConfig, CompilerError> {
+    let path = match &args.config {
+        Some(path) => path.clone(),
+        None => args.root.join("Misty.toml"),
+    };
+    if args.config.is_none() && !path.exists() {
+        return Ok(LintConfig::default());
+    }
+
+    let source = fs::read_to_string(&path).map_err(|source| CompilerError::Read {
+        path: path.clone(),
+        source,
+    })?;
+    Ok(LintConfig::from_toml(&source)?)
+}
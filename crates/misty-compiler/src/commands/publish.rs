@@ -0,0 +1,32 @@
+use crate::CompilerError;
+use crate::project::Project;
+use crate::registry::RegistryArgs;
+use clap::Args;
+use misty_core::registry::publish;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+#[derive(Args)]
+pub struct PublishArgs {
+    /// Root directory of the project, containing its `Misty.toml`.
+    #[arg(default_value = ".")]
+    root: PathBuf,
+
+    #[command(flatten)]
+    registry: RegistryArgs,
+}
+
+/// Publishes the package of the project to a registry, once validated.
+pub fn run(args: PublishArgs) -> Result<ExitCode, CompilerError> {
+    let project = Project::load(&args.root)?;
+    let Some(manifest) = &project.manifest else {
+        return Err(CompilerError::MissingManifest(args.root));
+    };
+    project.print_warnings();
+
+    let registry = args.registry.require_registry(&args.root, manifest)?;
+    let entry = publish(&registry, &args.root, manifest)?;
+    println!("Published {} {}", entry.name, entry.version);
+
+    Ok(ExitCode::SUCCESS)
+}
@@ -0,0 +1,66 @@
+use misty_core::lint::LintConfigError;
+use misty_core::lockfile::LockfileError;
+use misty_core::manifest::{ManifestError, WorkspaceError};
+use misty_core::registry::RegistryError;
+use misty_parser::LoadError;
+use std::path::PathBuf;
+
+/// Errors stopping a command of the compiler.
+///
+/// Errors found in Misty files are not part of them, they are reported as diagnostics instead.
+#[derive(Debug, thiserror::Error)]
+pub enum CompilerError {
+    #[error("Failed to read `{path}`: {source}")]
+    Read {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("Failed to write `{path}`: {source}")]
+    Write {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("The project has {0} error(s), see the diagnostics above")]
+    InvalidProject(usize),
+
+    #[error(transparent)]
+    Load(#[from] LoadError),
+
+    #[error(transparent)]
+    LintConfig(#[from] LintConfigError),
+
+    #[error(transparent)]
+    Manifest(#[from] ManifestError),
+
+    #[error(transparent)]
+    Workspace(#[from] WorkspaceError),
+
+    #[error("No `Misty.toml` manifest found in `{0}`")]
+    MissingManifest(PathBuf),
+
+    #[error(transparent)]
+    Registry(#[from] RegistryError),
+
+    #[error(transparent)]
+    Lockfile(#[from] LockfileError),
+
+    #[error("{0}, run `mistyc fetch` to update `Misty.lock`")]
+    OutdatedLockfile(LockfileError),
+
+    #[error("No `Misty.lock` lockfile found in `{0}`")]
+    MissingLockfile(PathBuf),
+
+    #[error("No registry configured, add a `[registry]` table to `Misty.toml` or use `--registry`")]
+    MissingRegistry,
+
+    #[error("The generator `{0}` is unknown")]
+    UnknownGenerator(String),
+
+    #[error("The generator `{generator}` failed: {source}")]
+    Generate {
+        generator: String,
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+}
@@ -0,0 +1,22 @@
+use crate::CompilerError;
+use misty_parser::find_misty_files;
+use std::path::PathBuf;
+
+/// Collects the Misty files found at the given paths, sorted.
+///
+/// Files are taken as-is, whatever their extension, while directories are searched recursively
+/// for `.misty` files. Hidden directories, like `.git`, are skipped.
+pub fn collect_misty_files(paths: &[PathBuf]) -> Result<Vec<PathBuf>, CompilerError> {
+    let mut files = Vec::new();
+    for path in paths {
+        if path.is_dir() {
+            files.extend(find_misty_files(path)?);
+        } else {
+            files.push(path.clone());
+        }
+    }
+    files.sort();
+    files.dedup();
+
+    Ok(files)
+}
@@ -1,3 +1,59 @@
-fn main() {
-    println!("Hello, world!");
+mod commands;
+mod error;
+mod files;
+mod project;
+mod registry;
+
+use clap::{Parser, Subcommand};
+use error::CompilerError;
+use std::process::ExitCode;
+
+/// Misty Compiler - CLI utility to build Misty projects.
+#[derive(Parser)]
+#[command(name = "mistyc", version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Adds a dependency to `Misty.toml` and installs it.
+    Add(commands::add::AddArgs),
+
+    /// Generates code with every generator configured in `Misty.toml`.
+    Build(commands::build::BuildArgs),
+
+    /// Validates a Misty project, reporting errors and warnings.
+    Check(commands::check::CheckArgs),
+
+    /// Installs the registry dependencies of a project and updates `Misty.lock`.
+    Fetch(commands::fetch::FetchArgs),
+
+    /// Formats Misty files with the canonical style.
+    Fmt(commands::fmt::FmtArgs),
+
+    /// Lints a Misty project.
+    Lint(commands::lint::LintArgs),
+
+    /// Publishes a package to a registry.
+    Publish(commands::publish::PublishArgs),
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let result = match cli.command {
+        Command::Add(args) => commands::add::run(args),
+        Command::Build(args) => commands::build::run(args),
+        Command::Check(args) => commands::check::run(args),
+        Command::Fetch(args) => commands::fetch::run(args),
+        Command::Fmt(args) => commands::fmt::run(args),
+        Command::Lint(args) => commands::lint::run(args),
+        Command::Publish(args) => commands::publish::run(args),
+    };
+
+    result.unwrap_or_else(|error| {
+        eprintln!("error: {error}");
+        ExitCode::FAILURE
+    })
 }
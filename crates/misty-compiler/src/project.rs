@@ -0,0 +1,107 @@
+use crate::CompilerError;
+use misty_codegen_rs::RustCodeGenerator;
+use misty_core::codegen::CodeGenerator;
+use misty_core::lockfile::Lockfile;
+use misty_core::manifest::{MANIFEST_FILE, Manifest, WorkspaceError};
+use misty_parser::{Diagnostic, LoadError, SourceFile, Workspace};
+use std::path::{Path, PathBuf};
+
+/// A Misty project, loaded from its root directory and validated.
+pub struct Project {
+    /// Root directory of the project.
+    pub root: PathBuf,
+
+    /// The manifest of the project, if it has a `Misty.toml`.
+    pub manifest: Option<Manifest>,
+
+    /// The validated workspace of the project, along with the file of every module.
+    pub workspace: Workspace,
+}
+
+impl Project {
+    /// Loads and validates every Misty file of a project directory.
+    ///
+    /// With a `Misty.toml` manifest, the source roots it declares are loaded instead of the whole
+    /// directory, along with the dependencies of the package, and the workspace knows its package.
+    /// The annotations of every generator shipped with the compiler are accepted.
+    ///
+    /// Loading, parser and validation errors are printed as diagnostics, and reported as a single
+    /// [CompilerError::InvalidProject]. Validation warnings are not printed, see
+    /// [Project::print_warnings].
+    pub fn load(root: &Path) -> Result<Self, CompilerError> {
+        let manifest = match root.join(MANIFEST_FILE).exists() {
+            true => Some(Manifest::load(root)?),
+            false => None,
+        };
+
+        let workspace = match &manifest {
+            Some(manifest) => manifest.load_workspace(root),
+            None => Workspace::load_dir(root).map_err(WorkspaceError::Load),
+        };
+        let mut workspace = workspace.map_err(|error| {
+            let WorkspaceError::Load(errors) = error else {
+                return CompilerError::from(error);
+            };
+            for error in &errors {
+                match error {
+                    LoadError::Parse { file, error } => {
+                        print_diagnostic(file, &Diagnostic::from(error.as_ref()))
+                    }
+                    _ => eprintln!("error: {error}"),
+                }
+            }
+            CompilerError::InvalidProject(errors.len())
+        })?;
+
+        for namespace in RustCodeGenerator.annotation_namespaces() {
+            workspace.add_annotation_namespace(namespace);
+        }
+
+        if let Err(validation_errors) = workspace.validate_all() {
+            for error in &validation_errors {
+                if let Some(file) = workspace.source_file(error.module()) {
+                    print_diagnostic(file, &Diagnostic::from(error));
+                }
+            }
+            return Err(CompilerError::InvalidProject(validation_errors.len()));
+        }
+
+        Ok(Self {
+            root: root.to_path_buf(),
+            manifest,
+            workspace,
+        })
+    }
+
+    /// Prints the validation warnings of the project as diagnostics.
+    pub fn print_warnings(&self) {
+        for warning in self.workspace.warnings() {
+            if let Some(file) = self.workspace.source_file(warning.module()) {
+                print_diagnostic(file, &Diagnostic::from(warning));
+            }
+        }
+    }
+
+    /// Writes the lockfile of the project, pinning the version and sources of every dependency
+    /// loaded.
+    pub fn lock(&self) -> Result<Lockfile, CompilerError> {
+        let lockfile = Lockfile::from_workspace(&self.workspace);
+        lockfile.save(&self.root)?;
+
+        Ok(lockfile)
+    }
+
+    /// Path of the file declaring a module, or the module path if it's unknown.
+    pub fn file_name(&self, module_path: &str) -> String {
+        self.workspace
+            .source_file(module_path)
+            .map(|file| file.path.display().to_string())
+            .unwrap_or_else(|| module_path.to_string())
+    }
+}
+
+/// Prints a diagnostic found in a file to the standard error.
+pub fn print_diagnostic(file: &SourceFile, diagnostic: &Diagnostic) {
+    let file_name = file.path.display().to_string();
+    eprint!("{}", diagnostic.render(&file_name, &file.source));
+}
@@ -0,0 +1,48 @@
+use crate::CompilerError;
+use clap::Args;
+use misty_core::manifest::{MANIFEST_FILE, Manifest};
+use misty_core::registry::DirectoryRegistry;
+use std::path::{Path, PathBuf};
+
+/// Arguments of the commands using a registry.
+#[derive(Args)]
+pub struct RegistryArgs {
+    /// Directory of the registry, instead of the one configured in `Misty.toml`.
+    #[arg(long)]
+    registry: Option<PathBuf>,
+}
+
+impl RegistryArgs {
+    /// The registry given on the command line, or configured by the manifest of the project, if
+    /// any.
+    pub fn registry(&self, root: &Path, manifest: &Manifest) -> Option<DirectoryRegistry> {
+        let configured = manifest
+            .registry
+            .as_ref()
+            .map(|registry| root.join(&registry.path));
+
+        self.registry
+            .clone()
+            .or(configured)
+            .map(|path| DirectoryRegistry::new(&path))
+    }
+
+    /// Same as [RegistryArgs::registry], failing when no registry is configured.
+    pub fn require_registry(
+        &self,
+        root: &Path,
+        manifest: &Manifest,
+    ) -> Result<DirectoryRegistry, CompilerError> {
+        self.registry(root, manifest)
+            .ok_or(CompilerError::MissingRegistry)
+    }
+}
+
+/// Loads the manifest of a project, which commands using a registry need.
+pub fn load_manifest(root: &Path) -> Result<Manifest, CompilerError> {
+    if !root.join(MANIFEST_FILE).exists() {
+        return Err(CompilerError::MissingManifest(root.to_path_buf()));
+    }
+
+    Ok(Manifest::load(root)?)
+}
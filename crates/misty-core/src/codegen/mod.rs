@@ -1,4 +1,5 @@
-use misty_parser::Workspace;
+use misty_parser::{ValidationError, Workspace};
+use serde::de::DeserializeOwned;
 use std::error::Error as StdError;
 use std::path::Path;
 
@@ -8,7 +9,32 @@ pub trait CodeGenerator {
     type Error: StdError + Send + Sync + 'static;
 
     /// Options required by the code generator.
-    type Options;
+    ///
+    /// They are read from the `[generators.<name>]` table of `Misty.toml`, see
+    /// [GeneratorConfig::options](crate::manifest::GeneratorConfig::options).
+    type Options: DeserializeOwned;
+
+    /// Name of the code generator, like `rust`, used by its table in `Misty.toml`.
+    fn name(&self) -> &str;
+
+    /// Namespaces of the annotations handled by the code generator, like `rust` for
+    /// `@rust(derive = "PartialEq")`.
+    ///
+    /// They must be registered with [Workspace::add_annotation_namespace] before validating the
+    /// workspace, so the validator passes them through.
+    fn annotation_namespaces(&self) -> &[&str] {
+        &[]
+    }
+
+    /// Checks a validated workspace against the constraints of the code generator, like the
+    /// arguments of its annotations or the names it reserves.
+    ///
+    /// Issues are reported like validation errors, at the node they were found, and can be
+    /// warnings, see [ValidationError::severity]. Code is not generated from workspaces with
+    /// errors.
+    fn validate(&self, _options: &Self::Options, _workspace: &Workspace) -> Vec<ValidationError> {
+        Vec::new()
+    }
 
     /// Generates the code for the given workspace.
     ///
@@ -0,0 +1,451 @@
+//! Canonical formatting of Misty files.
+//!
+//! The formatter works on the lossless concrete syntax tree, so comments are kept where they were
+//! written. Everything else is laid out the same way whatever the input:
+//! - Definitions are separated by a blank line, and their bodies are indented by four spaces.
+//! - Fields, variants and functions go on their own line, each doc comment and annotation too.
+//! - Enum variants always end with a comma, the last one included.
+//! - Imports are sorted by path, comments placed right above an import move along with it. The
+//!   types selected by an import stay on its line.
+//!
+//! Single blank lines between members are kept, so related members can still be grouped.
+use misty_parser::{NodeKind, ParserError, SyntaxElement, SyntaxNode, TokenKind, parse_cst};
+
+/// Indentation of a single level.
+const INDENT: &str = "    ";
+
+/// Formats a Misty source.
+///
+/// Formatting is idempotent: formatting the output again gives back the same output.
+#[tracing::instrument(skip(source))]
+pub fn format_source(source: &str) -> Result<String, ParserError> {
+    let file = parse_cst(source)?;
+    let mut chunks = collect_chunks(&file);
+
+    // Sort the imports by path, keeping them in the slots imports used to be in.
+    let slots = chunks
+        .iter()
+        .enumerate()
+        .filter(|(_, chunk)| chunk.is_import())
+        .map(|(index, _)| index)
+        .collect::<Vec<_>>();
+    let mut imports = slots
+        .iter()
+        .map(|&index| chunks[index].clone())
+        .collect::<Vec<_>>();
+    imports.sort_by_key(|chunk| chunk.node.map(import_path));
+    for (index, import) in slots.into_iter().zip(imports) {
+        chunks[index] = import;
+    }
+
+    // Print the chunks, separating them by a blank line, except between imports.
+    let mut output = String::new();
+    let mut previous: Option<&Chunk> = None;
+    for chunk in &chunks {
+        if let Some(previous) = previous {
+            output.push_str(if previous.is_import() && chunk.is_import() {
+                "\n"
+            } else {
+                "\n\n"
+            });
+        }
+        chunk.print(&mut output);
+        previous = Some(chunk);
+    }
+    if !output.is_empty() {
+        output.push('\n');
+    }
+
+    Ok(output)
+}
+
+/// An item of the file, along with the comments attached to it.
+///
+/// Comments separated from the next item by a blank line are chunks of their own.
+#[derive(Clone)]
+struct Chunk<'a> {
+    /// Comments placed on the lines right above the item.
+    leading: Vec<&'a str>,
+
+    /// The import or definition, `None` for detached comments.
+    node: Option<&'a SyntaxNode>,
+
+    /// A comment placed after the item, on its last line.
+    trailing: Option<&'a str>,
+}
+
+impl Chunk<'_> {
+    fn is_import(&self) -> bool {
+        self.node.is_some_and(|node| node.kind == NodeKind::Import)
+    }
+
+    /// Helper method to print the chunk, without a final line break.
+    fn print(&self, output: &mut String) {
+        let mut lines = self.leading.clone();
+        let item = self.node.map(print_item);
+        lines.extend(item.as_deref());
+        output.push_str(&lines.join("\n"));
+
+        if let Some(trailing) = self.trailing {
+            output.push(' ');
+            output.push_str(trailing);
+        }
+    }
+}
+
+/// Splits the top-level elements of a file into chunks.
+fn collect_chunks(file: &SyntaxNode) -> Vec<Chunk<'_>> {
+    let mut chunks = Vec::<Chunk>::new();
+    let mut comments = Vec::new();
+    let mut newlines = usize::MAX;
+
+    for child in &file.children {
+        match child {
+            SyntaxElement::Token(token) if token.kind == TokenKind::Whitespace => {
+                newlines = newlines.saturating_add(token.text.matches('\n').count());
+            }
+            SyntaxElement::Token(token) if token.is_trivia() => {
+                // A comment on the last line of an item is its trailing comment.
+                let last = chunks
+                    .last_mut()
+                    .filter(|chunk| chunk.node.is_some() && chunk.trailing.is_none());
+                match last {
+                    Some(chunk) if newlines == 0 && comments.is_empty() => {
+                        chunk.trailing = Some(token.text.trim_end());
+                    }
+                    _ => {
+                        // A blank line detaches the comments gathered so far from what follows.
+                        if newlines >= 2 && !comments.is_empty() {
+                            chunks.push(Chunk {
+                                leading: std::mem::take(&mut comments),
+                                node: None,
+                                trailing: None,
+                            });
+                        }
+                        comments.push(token.text.trim_end());
+                    }
+                }
+                newlines = 0;
+            }
+            SyntaxElement::Token(_) => newlines = 0,
+            SyntaxElement::Node(node) => {
+                if newlines >= 2 && !comments.is_empty() {
+                    chunks.push(Chunk {
+                        leading: std::mem::take(&mut comments),
+                        node: None,
+                        trailing: None,
+                    });
+                }
+                chunks.push(Chunk {
+                    leading: std::mem::take(&mut comments),
+                    node: Some(node),
+                    trailing: None,
+                });
+                newlines = 0;
+            }
+        }
+    }
+    if !comments.is_empty() {
+        chunks.push(Chunk {
+            leading: comments,
+            node: None,
+            trailing: None,
+        });
+    }
+
+    chunks
+}
+
+/// Path of an import, used to sort them.
+fn import_path(node: &SyntaxNode) -> String {
+    let path = node
+        .tokens()
+        .filter(|token| !token.is_trivia())
+        .skip(1)
+        .take_while(|token| token.kind == TokenKind::Identifier || token.text == ".")
+        .map(|token| token.text.as_str())
+        .collect::<String>();
+    path.trim_end_matches('.').to_string()
+}
+
+/// A token of an item, flattened out of the tree.
+struct Piece<'a> {
+    text: &'a str,
+    kind: TokenKind,
+
+    /// The node directly containing the token.
+    parent: NodeKind,
+
+    /// Number of line breaks found between this token and the previous one.
+    newlines: usize,
+
+    /// Whether this token ends an enum variant.
+    ends_variant: bool,
+
+    /// Whether this token ends an annotation.
+    ends_annotation: bool,
+}
+
+impl Piece<'_> {
+    fn is_comment(&self) -> bool {
+        matches!(self.kind, TokenKind::LineComment | TokenKind::BlockComment)
+    }
+}
+
+/// Flattens the tokens of a node, skipping whitespace but counting the line breaks it holds.
+fn flatten<'a>(node: &'a SyntaxNode, pieces: &mut Vec<Piece<'a>>, newlines: &mut usize) {
+    let start = pieces.len();
+    for child in &node.children {
+        match child {
+            SyntaxElement::Node(inner) => flatten(inner, pieces, newlines),
+            SyntaxElement::Token(token) if token.kind == TokenKind::Whitespace => {
+                *newlines += token.text.matches('\n').count();
+            }
+            SyntaxElement::Token(token) => {
+                pieces.push(Piece {
+                    text: token.text.trim_end(),
+                    kind: token.kind,
+                    parent: node.kind,
+                    newlines: *newlines,
+                    ends_variant: false,
+                    ends_annotation: false,
+                });
+                *newlines = 0;
+            }
+        }
+    }
+
+    if let Some(last) = pieces[start..].last_mut() {
+        last.ends_variant |= node.kind == NodeKind::EnumVariant;
+        last.ends_annotation |= node.kind == NodeKind::Annotation;
+    }
+}
+
+/// Output of a single item, keeping track of lines and indentation.
+#[derive(Default)]
+struct Printer {
+    output: String,
+    indent: usize,
+
+    /// Whether nothing was written on the current line yet.
+    line_start: bool,
+
+    /// Whether the next token goes on a new line.
+    break_line: bool,
+
+    /// Whether the next token is preceded by a space.
+    space: bool,
+}
+
+impl Printer {
+    /// Ends the current line, unless it's empty, and optionally leaves a blank line.
+    fn newline(&mut self, blank: bool) {
+        if !self.line_start {
+            self.output.push('\n');
+            if blank {
+                self.output.push('\n');
+            }
+        }
+        self.line_start = true;
+        self.break_line = false;
+        self.space = false;
+    }
+
+    /// Writes some text on the current line, indenting it when it starts the line.
+    fn write(&mut self, text: &str) {
+        if self.line_start {
+            self.output.push_str(&INDENT.repeat(self.indent));
+        } else if self.space {
+            self.output.push(' ');
+        }
+        self.output.push_str(text);
+        self.line_start = false;
+        self.space = false;
+    }
+}
+
+/// Prints an import or a definition, without a final line break.
+fn print_item(node: &SyntaxNode) -> String {
+    let mut pieces = Vec::new();
+    flatten(node, &mut pieces, &mut 0);
+    move_separators_before_comments(&mut pieces);
+
+    let mut printer = Printer {
+        line_start: true,
+        ..Default::default()
+    };
+    let mut previous: Option<&Piece> = None;
+    for (index, piece) in pieces.iter().enumerate() {
+        // Blank lines are only kept between members, not right after an opening brace.
+        let blank = piece.newlines >= 2 && previous.is_some_and(|previous| previous.text != "{");
+
+        match piece.kind {
+            TokenKind::LineComment | TokenKind::BlockComment => {
+                if piece.newlines == 0 && !printer.line_start {
+                    printer.space = true;
+                    printer.write(piece.text);
+                    if piece.kind == TokenKind::LineComment {
+                        printer.newline(false);
+                    } else {
+                        printer.space = true;
+                    }
+                } else {
+                    printer.newline(blank && printer.break_line);
+                    printer.write(piece.text);
+                    printer.newline(false);
+                }
+                continue;
+            }
+            TokenKind::DocComment => {
+                printer.newline(blank && printer.break_line);
+                printer.write(piece.text);
+                printer.newline(false);
+                previous = Some(piece);
+                continue;
+            }
+            _ => (),
+        }
+
+        // Types selected by an import stay on the line of the import, without a trailing comma.
+        if piece.parent == NodeKind::ImportItems {
+            let last = pieces.get(index + 1).is_some_and(|next| next.text == "}");
+            if !(piece.text == "," && last) {
+                printer.space |= previous.is_some_and(|previous| needs_space(previous, piece));
+                printer.write(piece.text);
+            }
+            previous = Some(piece);
+            continue;
+        }
+
+        match piece.text {
+            "}" => {
+                printer.indent = printer.indent.saturating_sub(1);
+                let empty =
+                    previous.is_some_and(|previous| previous.text == "{") && !printer.line_start;
+                if !empty {
+                    printer.newline(false);
+                }
+                printer.write("}");
+            }
+            _ => {
+                if printer.break_line {
+                    printer.newline(blank);
+                } else if !printer.line_start {
+                    printer.space |= previous.is_some_and(|previous| needs_space(previous, piece));
+                }
+                printer.write(piece.text);
+            }
+        }
+
+        // Decide what follows the token.
+        match piece.text {
+            "{" => {
+                printer.indent += 1;
+                printer.break_line = true;
+            }
+            ";" => printer.break_line = true,
+            "," if piece.parent == NodeKind::Enum => printer.break_line = true,
+            _ => (),
+        }
+        if piece.ends_annotation {
+            printer.break_line = true;
+        }
+
+        // Enum variants always end with a comma, written before their trailing comments.
+        let next = pieces[index + 1..].iter().find(|next| !next.is_comment());
+        if piece.ends_variant && next.is_some_and(|next| next.text == "}") {
+            printer.write(",");
+            printer.break_line = true;
+        }
+
+        previous = Some(piece);
+    }
+
+    printer.output
+}
+
+/// Moves the separators and closing parentheses following comments before them.
+///
+/// A line comment ends its line, the separator following it would start the next one, out of its
+/// indentation: `A // a\n, B` is printed as `A, // a\nB` instead.
+fn move_separators_before_comments(pieces: &mut [Piece]) {
+    for index in 0..pieces.len() {
+        if !matches!(pieces[index].text, "," | ";" | ")") || pieces[index].is_comment() {
+            continue;
+        }
+
+        let first_comment = pieces[..index]
+            .iter()
+            .rposition(|piece| !piece.is_comment())
+            .map_or(0, |position| position + 1);
+        if first_comment < index {
+            pieces[index].newlines = 0;
+            pieces[first_comment..=index].rotate_right(1);
+        }
+    }
+}
+
+/// Checks if two consecutive tokens are separated by a space.
+fn needs_space(previous: &Piece, piece: &Piece) -> bool {
+    let selection =
+        |piece: &Piece, brace| piece.parent == NodeKind::ImportItems && piece.text == brace;
+
+    !matches!(previous.text, "<" | "(" | "." | "@")
+        && !matches!(piece.text, ">" | ")" | "." | "," | ";" | ":" | "<" | "(")
+        && !selection(previous, "{")
+        && !selection(piece, "}")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::formatter::format_source;
+
+    #[test]
+    fn it_formats_files() {
+        let source = "// Header.\n\nimport users;  import auth . tokens ; // Tokens.\n\
+                      import ml . analyzer.{ Analysis,Report, } ;import jobs as j;\n\
+                      /// A person.\n@deprecated(\"use v2\") schema Person{owner:users.User;\n\n\n\
+                      // The tags.\ntags : map< str,vec<u8> > = none; /* Inline. */ }\n\
+                      enum Role { ADMIN = 1, GUEST }\nschema Empty {}\n\
+                      interface Jobs { @idempotent fn list( stream u32 ):option<str>; }\n\
+                      // The end.";
+        let expected = "// Header.\n\nimport auth.tokens; // Tokens.\nimport jobs as j;\n\
+                        import ml.analyzer.{Analysis, Report};\nimport users;\n\n\
+                        /// A person.\n@deprecated(\"use v2\")\nschema Person {\n    \
+                        owner: users.User;\n\n    // The tags.\n    \
+                        tags: map<str, vec<u8>> = none; /* Inline. */\n}\n\n\
+                        enum Role {\n    ADMIN = 1,\n    GUEST,\n}\n\nschema Empty {}\n\n\
+                        interface Jobs {\n    @idempotent\n    \
+                        fn list(stream u32): option<str>;\n}\n\n// The end.\n";
+
+        let formatted = format_source(source).unwrap();
+        assert_eq!(formatted, expected);
+        assert_eq!(format_source(&formatted).unwrap(), formatted);
+
+        // Separators, like the trailing comma of the last variant, go before comments.
+        let cases = [
+            (
+                "enum E { A, B // c\n}",
+                "enum E {\n    A,\n    B, // c\n}\n",
+            ),
+            (
+                "enum E { A, /* c */ B /* d */ }",
+                "enum E {\n    A, /* c */\n    B, /* d */\n}\n",
+            ),
+            (
+                "enum E { A // a\n, B }",
+                "enum E {\n    A, // a\n    B,\n}\n",
+            ),
+            (
+                "@rust(derive = \"Hash\" // c\n) schema S {}",
+                "@rust(derive = \"Hash\") // c\nschema S {}\n",
+            ),
+        ];
+
+        for (source, expected) in cases {
+            let formatted = format_source(source).unwrap();
+            assert_eq!(formatted, expected);
+            assert_eq!(format_source(&formatted).unwrap(), formatted);
+        }
+    }
+}
@@ -1 +1,6 @@
 pub mod codegen;
+pub mod formatter;
+pub mod lint;
+pub mod lockfile;
+pub mod manifest;
+pub mod registry;
@@ -0,0 +1,67 @@
+use crate::lint::LintRule;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+/// Errors raised while reading the lint configuration.
+#[derive(Debug, thiserror::Error)]
+pub enum LintConfigError {
+    #[error("The lint configuration is invalid: {0}")]
+    Toml(#[from] toml::de::Error),
+
+    #[error("The lint rule `{0}` is unknown")]
+    UnknownRule(String),
+}
+
+/// Rules enabled in the linter.
+///
+/// Every rule is enabled by default. They are disabled from the `[lint]` table of `Misty.toml`:
+///
+/// ```toml
+/// [lint]
+/// empty_definition = false
+/// vec_of_option = false
+/// ```
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(transparent)]
+pub struct LintConfig {
+    rules: BTreeMap<String, bool>,
+}
+
+/// Helper struct to only read the `[lint]` table of `Misty.toml`.
+#[derive(Deserialize)]
+struct ConfigFile {
+    #[serde(default)]
+    lint: LintConfig,
+}
+
+impl LintConfig {
+    /// Reads the `[lint]` table of a `Misty.toml` source, other tables are ignored.
+    pub fn from_toml(source: &str) -> Result<Self, LintConfigError> {
+        let config = toml::from_str::<ConfigFile>(source)?.lint;
+        config.validate()?;
+
+        Ok(config)
+    }
+
+    /// Checks that every configured rule exists.
+    pub fn validate(&self) -> Result<(), LintConfigError> {
+        match self
+            .rules
+            .keys()
+            .find(|name| LintRule::from_name(name).is_none())
+        {
+            Some(name) => Err(LintConfigError::UnknownRule(name.clone())),
+            None => Ok(()),
+        }
+    }
+
+    /// Checks if a rule is enabled.
+    pub fn is_enabled(&self, rule: LintRule) -> bool {
+        self.rules.get(rule.name()).copied().unwrap_or(true)
+    }
+
+    /// Enables or disables a rule.
+    pub fn set_enabled(&mut self, rule: LintRule, enabled: bool) {
+        self.rules.insert(rule.name().to_string(), enabled);
+    }
+}
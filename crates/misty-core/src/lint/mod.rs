@@ -0,0 +1,187 @@
+//! The lint module contains the Misty linter.
+//!
+//! Lints are not errors: the workspace is valid and generates working code, but something in it
+//! is likely a mistake or will cause trouble later, like an `option<option<T>>` whose two kinds
+//! of "none" cannot be told apart on the wire. Every rule can be disabled from the `[lint]` table
+//! of the project `Misty.toml`, see [LintConfig].
+//!
+//! The workspace is expected to be validated before being linted.
+mod config;
+mod rules;
+
+pub use config::{LintConfig, LintConfigError};
+
+use misty_ast::Span;
+use misty_parser::Workspace;
+use std::fmt;
+
+/// Rules checked by the linter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LintRule {
+    /// An imported module is never used by any type reference of the file.
+    ///
+    /// Found by the validator, which reports it as a warning.
+    UnusedImport,
+
+    /// A schema, enum, union or interface declares no members.
+    EmptyDefinition,
+
+    /// An `option<option<T>>`, whose inner and outer `none` look the same on the wire.
+    NestedOption,
+
+    /// A `vec<option<T>>`, usually meant to be a `vec<T>` without the missing elements.
+    VecOfOption,
+
+    /// A function streaming its input without returning anything, so callers never know when the
+    /// stream was fully processed.
+    StreamWithoutResponse,
+
+    /// Two names becoming the same once converted to the case of the generated code.
+    NameCollision,
+
+    /// A type reference to a deprecated definition, from something not deprecated itself.
+    DeprecatedUsage,
+}
+
+impl LintRule {
+    /// Every rule, in the order they are checked.
+    pub const ALL: [LintRule; 7] = [
+        LintRule::UnusedImport,
+        LintRule::EmptyDefinition,
+        LintRule::NestedOption,
+        LintRule::VecOfOption,
+        LintRule::StreamWithoutResponse,
+        LintRule::NameCollision,
+        LintRule::DeprecatedUsage,
+    ];
+
+    /// Name of the rule, as used in the configuration and in reports.
+    pub fn name(&self) -> &'static str {
+        match self {
+            LintRule::UnusedImport => "unused_import",
+            LintRule::EmptyDefinition => "empty_definition",
+            LintRule::NestedOption => "nested_option",
+            LintRule::VecOfOption => "vec_of_option",
+            LintRule::StreamWithoutResponse => "stream_without_response",
+            LintRule::NameCollision => "name_collision",
+            LintRule::DeprecatedUsage => "deprecated_usage",
+        }
+    }
+
+    /// Finds a rule by its name.
+    pub fn from_name(name: &str) -> Option<LintRule> {
+        LintRule::ALL.into_iter().find(|rule| rule.name() == name)
+    }
+}
+
+impl fmt::Display for LintRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// A single finding of the linter.
+#[derive(Debug, Clone)]
+pub struct Lint {
+    /// The rule that found it.
+    pub rule: LintRule,
+
+    /// Path of the module where it was found.
+    pub module: String,
+
+    /// Name of the definition where it was found, if it was found inside one.
+    pub definition: Option<String>,
+
+    /// Description of the finding.
+    pub message: String,
+
+    /// Location of the offending node in its module.
+    pub span: Span,
+}
+
+/// Lints every package-local module of a workspace with the rules enabled in `config`.
+///
+/// Lints are sorted by module path, then by location.
+#[tracing::instrument(skip(workspace, config))]
+pub fn lint_workspace(workspace: &Workspace, config: &LintConfig) -> Vec<Lint> {
+    let mut lints = Vec::new();
+
+    for (module_path, file) in workspace.package_local_modules() {
+        let ctx = rules::LintContext {
+            workspace,
+            warnings: workspace.warnings(),
+            module_path,
+            file,
+        };
+        for rule in LintRule::ALL {
+            if config.is_enabled(rule) {
+                rules::check(&ctx, rule, &mut lints);
+            }
+        }
+    }
+
+    lints.sort_by(|a, b| (&a.module, a.span.start).cmp(&(&b.module, b.span.start)));
+    lints
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lint::{LintConfig, LintRule, lint_workspace};
+    use misty_parser::{Workspace, parse};
+
+    #[test]
+    fn it_lints_workspaces() {
+        let users = "@deprecated(\"use UserV2\") schema User { id: u64; }\nschema Empty {}";
+        let jobs = "import users;\nimport unused;\n\
+                    schema Job { owner: users.User; tags: option<option<str>>; \
+                    steps: vec<option<u8>>; }\n\
+                    @deprecated schema OldJob { owner: users.User; }\n\
+                    schema HttpJob {} schema HTTPJob {}\n\
+                    enum Mode { MODE_1, MODE1 }\n\
+                    interface Jobs { fn upload(stream Job); fn cancel(u64); }";
+
+        let mut workspace = Workspace::new();
+        workspace.add_local_module("users", parse(users).unwrap());
+        workspace.add_local_module("unused", parse("").unwrap());
+        workspace.add_local_module("jobs", parse(jobs).unwrap());
+        workspace.validate().unwrap();
+
+        let lints = lint_workspace(&workspace, &LintConfig::default());
+        let summary = lints
+            .iter()
+            .map(|lint| (lint.module.as_str(), lint.rule))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            vec![
+                ("jobs", LintRule::UnusedImport),
+                ("jobs", LintRule::DeprecatedUsage),
+                ("jobs", LintRule::NestedOption),
+                ("jobs", LintRule::VecOfOption),
+                ("jobs", LintRule::EmptyDefinition),
+                ("jobs", LintRule::EmptyDefinition),
+                ("jobs", LintRule::NameCollision),
+                ("jobs", LintRule::NameCollision),
+                ("jobs", LintRule::StreamWithoutResponse),
+                ("users", LintRule::EmptyDefinition),
+            ]
+        );
+        assert_eq!(
+            lints[1].message,
+            "`Job.owner` uses the deprecated `users.User`: use UserV2"
+        );
+        assert_eq!(
+            lints[6].message,
+            "`HttpJob` and `HTTPJob` both become `HTTP_JOB` in generated code"
+        );
+
+        let config = LintConfig::from_toml(
+            "[package]\nname = \"jobs\"\n\n[lint]\nempty_definition = false\n\
+             name_collision = false\n",
+        )
+        .unwrap();
+        assert!(!config.is_enabled(LintRule::EmptyDefinition));
+        assert_eq!(lint_workspace(&workspace, &config).len(), 5);
+        assert!(LintConfig::from_toml("[lint]\nunknown_rule = false").is_err());
+    }
+}
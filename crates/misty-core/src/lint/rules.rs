@@ -0,0 +1,327 @@
+use crate::lint::{Lint, LintRule};
+use heck::{ToPascalCase, ToShoutySnakeCase};
+use misty_ast::{Annotation, ContainerType, DataType, Definition, File, Literal, Span};
+use misty_parser::{ValidationError, Workspace};
+use std::collections::HashMap;
+
+/// The module being linted, along with its workspace.
+pub struct LintContext<'a> {
+    /// The workspace of the module, giving access to every module visible from it.
+    pub workspace: &'a Workspace,
+
+    /// Warnings found when validating the workspace, for every module.
+    pub warnings: &'a [ValidationError],
+
+    /// Path of the module being linted.
+    pub module_path: &'a str,
+
+    /// The module being linted.
+    pub file: &'a File,
+}
+
+impl LintContext<'_> {
+    /// Helper method to build a lint found in this module.
+    fn lint(&self, rule: LintRule, definition: Option<&str>, message: String, span: Span) -> Lint {
+        Lint {
+            rule,
+            module: self.module_path.to_string(),
+            definition: definition.map(str::to_string),
+            message,
+            span,
+        }
+    }
+}
+
+/// A data type held by a field, a union variant, or a function argument or return type.
+struct Member<'a> {
+    /// The definition declaring the member.
+    definition: &'a Definition,
+
+    /// Name of the member.
+    name: &'a str,
+
+    data_type: &'a DataType,
+    annotations: &'a [Annotation],
+    span: Span,
+}
+
+/// Every member holding a data type in a definition.
+fn members(definition: &Definition) -> Vec<Member<'_>> {
+    let member = |name, data_type, annotations, span| Member {
+        definition,
+        name,
+        data_type,
+        annotations,
+        span,
+    };
+
+    match definition {
+        Definition::Schema(schema) => schema
+            .fields
+            .iter()
+            .map(|field| {
+                member(
+                    &field.name,
+                    &field.field_type,
+                    &field.annotations,
+                    field.span,
+                )
+            })
+            .collect(),
+        Definition::Union(union) => union
+            .variants
+            .iter()
+            .map(|variant| {
+                member(
+                    &variant.name,
+                    &variant.variant_type,
+                    &variant.annotations,
+                    variant.span,
+                )
+            })
+            .collect(),
+        Definition::Interface(interface) => interface
+            .functions
+            .iter()
+            .flat_map(|function| {
+                std::iter::once(&function.input)
+                    .chain(&function.output)
+                    .map(|(_, data_type)| {
+                        member(
+                            &function.name,
+                            data_type,
+                            &function.annotations,
+                            function.span,
+                        )
+                    })
+            })
+            .collect(),
+        Definition::Enum(_) => Vec::new(),
+    }
+}
+
+/// Calls `visit` on a data type and every data type nested in it.
+fn walk<'a>(data_type: &'a DataType, visit: &mut impl FnMut(&'a DataType)) {
+    visit(data_type);
+    if let DataType::Container(_, inner) = data_type {
+        inner.iter().for_each(|inner| walk(inner, visit));
+    }
+}
+
+/// Finds the `@deprecated` annotation among some annotations.
+fn deprecation(annotations: &[Annotation]) -> Option<&Annotation> {
+    annotations
+        .iter()
+        .find(|annotation| annotation.name == "deprecated")
+}
+
+/// Checks a single rule on the module of the context.
+pub fn check(ctx: &LintContext, rule: LintRule, lints: &mut Vec<Lint>) {
+    match rule {
+        LintRule::UnusedImport => check_unused_imports(ctx, lints),
+        LintRule::EmptyDefinition => check_empty_definitions(ctx, lints),
+        LintRule::NestedOption | LintRule::VecOfOption => check_containers(ctx, rule, lints),
+        LintRule::StreamWithoutResponse => check_streams(ctx, lints),
+        LintRule::NameCollision => check_name_collisions(ctx, lints),
+        LintRule::DeprecatedUsage => check_deprecated_usage(ctx, lints),
+    }
+}
+
+/// Reports the imports never used by any type reference of the module.
+///
+/// The validator already finds them, so this turns its warnings into lints.
+fn check_unused_imports(ctx: &LintContext, lints: &mut Vec<Lint>) {
+    for warning in ctx.warnings {
+        if warning.module() != ctx.module_path {
+            continue;
+        }
+
+        let message = match warning {
+            ValidationError::UnusedImport { import, .. } => {
+                format!("the module `{import}` is imported but never used")
+            }
+            ValidationError::UnusedImportedType { import, name, .. } => {
+                format!("the type `{name}` is imported from `{import}` but never used")
+            }
+            _ => continue,
+        };
+        lints.push(ctx.lint(LintRule::UnusedImport, None, message, warning.span()));
+    }
+}
+
+/// Reports the definitions declaring no members.
+fn check_empty_definitions(ctx: &LintContext, lints: &mut Vec<Lint>) {
+    for definition in &ctx.file.definitions {
+        let (kind, empty) = match definition {
+            Definition::Schema(schema) => ("schema", schema.fields.is_empty()),
+            Definition::Enum(misty_enum) => ("enum", misty_enum.variants.is_empty()),
+            Definition::Union(union) => ("union", union.variants.is_empty()),
+            Definition::Interface(interface) => ("interface", interface.functions.is_empty()),
+        };
+
+        if empty {
+            let name = definition.name();
+            let message = format!("the {kind} `{name}` is empty");
+            lints.push(ctx.lint(
+                LintRule::EmptyDefinition,
+                Some(name),
+                message,
+                definition.span(),
+            ));
+        }
+    }
+}
+
+/// Reports the `option<option<T>>` or the `vec<option<T>>` types, depending on the rule.
+fn check_containers(ctx: &LintContext, rule: LintRule, lints: &mut Vec<Lint>) {
+    let outer = match rule {
+        LintRule::NestedOption => ContainerType::Option,
+        _ => ContainerType::Vec,
+    };
+
+    for definition in &ctx.file.definitions {
+        for member in members(definition) {
+            walk(member.data_type, &mut |data_type| {
+                let DataType::Container(container, inner) = data_type else {
+                    return;
+                };
+                if *container != outer
+                    || !matches!(
+                        inner.first(),
+                        Some(DataType::Container(ContainerType::Option, _))
+                    )
+                {
+                    return;
+                }
+
+                let definition = member.definition.name();
+                let message = match rule {
+                    LintRule::NestedOption => format!(
+                        "`{}.{}` uses `{data_type}`, whose inner and outer `none` cannot be told \
+                         apart on the wire",
+                        definition, member.name
+                    ),
+                    _ => format!(
+                        "`{}.{}` uses `{data_type}`, consider leaving the missing elements out",
+                        definition, member.name
+                    ),
+                };
+                lints.push(ctx.lint(rule, Some(definition), message, member.span));
+            });
+        }
+    }
+}
+
+/// Reports the functions streaming their input without returning anything.
+fn check_streams(ctx: &LintContext, lints: &mut Vec<Lint>) {
+    for definition in &ctx.file.definitions {
+        let Definition::Interface(interface) = definition else {
+            continue;
+        };
+
+        for function in &interface.functions {
+            if function.input.0 && function.output.is_none() {
+                let message = format!(
+                    "`{}.{}` streams its input but returns nothing, callers cannot know when the \
+                     stream was processed",
+                    interface.name, function.name
+                );
+                let rule = LintRule::StreamWithoutResponse;
+                lints.push(ctx.lint(rule, Some(&interface.name), message, function.span));
+            }
+        }
+    }
+}
+
+/// Reports the names becoming the same once converted to the case used in generated code.
+///
+/// Definitions are checked in SCREAMING_SNAKE_CASE, used for their discriminator constants, and
+/// enum variants in PascalCase.
+fn check_name_collisions(ctx: &LintContext, lints: &mut Vec<Lint>) {
+    let mut report = |definition: Option<&str>, first: &str, second: &str, converted, span| {
+        let message =
+            format!("`{first}` and `{second}` both become `{converted}` in generated code");
+        lints.push(ctx.lint(LintRule::NameCollision, definition, message, span));
+    };
+
+    let mut definitions = HashMap::new();
+    for definition in &ctx.file.definitions {
+        let name = definition.name();
+        let converted = name.to_shouty_snake_case();
+        match definitions.get(&converted) {
+            Some(&first) if first != name => {
+                report(Some(name), first, name, converted, definition.span())
+            }
+            _ => {
+                definitions.insert(converted, name);
+            }
+        }
+    }
+
+    for definition in &ctx.file.definitions {
+        let Definition::Enum(misty_enum) = definition else {
+            continue;
+        };
+
+        let mut variants = HashMap::new();
+        for variant in &misty_enum.variants {
+            let converted = variant.name.to_pascal_case();
+            match variants.get(&converted) {
+                Some(&first) if first != variant.name.as_str() => report(
+                    Some(&misty_enum.name),
+                    first,
+                    &variant.name,
+                    converted,
+                    variant.span,
+                ),
+                _ => {
+                    variants.insert(converted, variant.name.as_str());
+                }
+            }
+        }
+    }
+}
+
+/// Reports the references to deprecated definitions.
+///
+/// References made from deprecated members or definitions are expected to go away along with
+/// them, so they are not reported.
+fn check_deprecated_usage(ctx: &LintContext, lints: &mut Vec<Lint>) {
+    for definition in &ctx.file.definitions {
+        if deprecation(definition.annotations()).is_some() {
+            continue;
+        }
+
+        for member in members(definition) {
+            if deprecation(member.annotations).is_some() {
+                continue;
+            }
+
+            walk(member.data_type, &mut |data_type| {
+                let DataType::UserType(type_name) = data_type else {
+                    return;
+                };
+                let (module_path, name) = ctx.file.qualify(ctx.module_path, type_name);
+                let Some(annotation) = ctx
+                    .workspace
+                    .module(module_path)
+                    .and_then(|file| file.definitions.iter().find(|target| target.name() == name))
+                    .and_then(|target| deprecation(target.annotations()))
+                else {
+                    return;
+                };
+
+                let mut message = format!(
+                    "`{}.{}` uses the deprecated `{type_name}`",
+                    definition.name(),
+                    member.name
+                );
+                if let Some(Literal::Str(reason)) = annotation.positional().next() {
+                    message.push_str(&format!(": {reason}"));
+                }
+                let rule = LintRule::DeprecatedUsage;
+                lints.push(ctx.lint(rule, Some(definition.name()), message, member.span));
+            });
+        }
+    }
+}
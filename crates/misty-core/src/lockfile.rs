@@ -0,0 +1,316 @@
+//! The lockfile module reads and writes `Misty.lock`, the exact versions and sources of the
+//! dependencies of a project.
+//!
+//! The lockfile is written every time dependencies are resolved, and read back by the next
+//! resolution so the same versions are installed on every machine until a dependency is added or
+//! its requirement changes:
+//!
+//! ```toml
+//! version = 1
+//!
+//! [[package]]
+//! name = "common_types"
+//! version = "1.2.0"
+//! checksum = "2f8c..."
+//! ```
+//!
+//! Checksums are the blake3 hashes of the Misty files of the packages, see [sources_checksum].
+//! Before generating code, the compiler checks the dependencies it loaded against the lockfile with
+//! [Lockfile::verify], so code is only generated from the exact sources pinned by the lockfile.
+use misty_parser::Workspace;
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Name of the lockfile, at the root of a project.
+pub const LOCKFILE: &str = "Misty.lock";
+
+/// Version of the lockfile format.
+const LOCKFILE_VERSION: u32 = 1;
+
+/// Errors raised while reading or writing a lockfile.
+#[derive(Debug, thiserror::Error)]
+pub enum LockfileError {
+    #[error("Failed to read `{path}`: {source}")]
+    Read {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("Failed to write `{path}`: {source}")]
+    Write {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("The lockfile is invalid: {0}")]
+    Toml(#[from] toml::de::Error),
+
+    #[error("The lockfile can't be written: {0}")]
+    Serialize(#[from] toml::ser::Error),
+
+    #[error("The lockfile version {0} is not supported")]
+    Version(u32),
+
+    #[error("The dependency `{0}` is not in the lockfile")]
+    Unlocked(String),
+
+    #[error("The dependency `{name}` is {found}, but the lockfile pins {locked}")]
+    Outdated {
+        name: String,
+        locked: Version,
+        found: Version,
+    },
+
+    #[error(
+        "The sources of `{name}` {version} have the hash {found}, but the lockfile pins {expected}"
+    )]
+    Checksum {
+        name: String,
+        version: Version,
+        expected: String,
+        found: String,
+    },
+}
+
+/// The exact versions and sources of the dependencies of a project.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Lockfile {
+    /// Version of the lockfile format.
+    version: u32,
+
+    /// Every package installed, sorted by name.
+    #[serde(default, rename = "package")]
+    pub packages: Vec<LockedPackage>,
+}
+
+/// A package pinned by the lockfile.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockedPackage {
+    /// Name of the package.
+    pub name: String,
+
+    /// Exact version of the package.
+    pub version: Version,
+
+    /// blake3 hash of the sources of the package, in hexadecimal.
+    pub checksum: String,
+}
+
+impl Default for Lockfile {
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
+
+impl Lockfile {
+    /// Creates a lockfile pinning some packages.
+    pub fn new(mut packages: Vec<LockedPackage>) -> Self {
+        packages.sort_by(|a, b| a.name.cmp(&b.name));
+        Self {
+            version: LOCKFILE_VERSION,
+            packages,
+        }
+    }
+
+    /// Creates a lockfile pinning every dependency of a workspace, recursively.
+    ///
+    /// Dependencies used by several packages are only pinned once.
+    pub fn from_workspace(workspace: &Workspace) -> Self {
+        let mut packages = Vec::new();
+        locked_dependencies(workspace, &mut packages);
+        packages.sort_by(|a, b| a.name.cmp(&b.name));
+        packages.dedup_by(|a, b| a.name == b.name);
+
+        Self::new(packages)
+    }
+
+    /// Reads a lockfile from its source.
+    pub fn from_toml(source: &str) -> Result<Self, LockfileError> {
+        let lockfile = toml::from_str::<Self>(source)?;
+        if lockfile.version != LOCKFILE_VERSION {
+            return Err(LockfileError::Version(lockfile.version));
+        }
+
+        Ok(lockfile)
+    }
+
+    /// Writes the lockfile, sorted so it only changes when a package does.
+    pub fn to_toml(&self) -> Result<String, LockfileError> {
+        let source = toml::to_string(self)?;
+        Ok(format!(
+            "# This file is generated by mistyc, do not edit it.\n{source}"
+        ))
+    }
+
+    /// Reads the lockfile at the root of a project, if there is one.
+    pub fn load(root: &Path) -> Result<Option<Self>, LockfileError> {
+        let path = root.join(LOCKFILE);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let source =
+            fs::read_to_string(&path).map_err(|source| LockfileError::Read { path, source })?;
+
+        Self::from_toml(&source).map(Some)
+    }
+
+    /// Writes the lockfile at the root of a project.
+    pub fn save(&self, root: &Path) -> Result<(), LockfileError> {
+        let path = root.join(LOCKFILE);
+        fs::write(&path, self.to_toml()?).map_err(|source| LockfileError::Write { path, source })
+    }
+
+    /// Finds the pinned version of a package.
+    pub fn package(&self, name: &str) -> Option<&LockedPackage> {
+        self.packages.iter().find(|package| package.name == name)
+    }
+
+    /// Checks that every dependency of a workspace has the version and sources pinned by the
+    /// lockfile.
+    ///
+    /// Packages pinned by the lockfile but no longer used are ignored.
+    pub fn verify(&self, workspace: &Workspace) -> Result<(), LockfileError> {
+        let mut packages = Vec::new();
+        locked_dependencies(workspace, &mut packages);
+
+        for package in packages {
+            let Some(locked) = self.package(&package.name) else {
+                return Err(LockfileError::Unlocked(package.name));
+            };
+            if locked.version != package.version {
+                return Err(LockfileError::Outdated {
+                    name: package.name,
+                    locked: locked.version.clone(),
+                    found: package.version,
+                });
+            }
+            if locked.checksum != package.checksum {
+                return Err(LockfileError::Checksum {
+                    name: package.name,
+                    version: package.version,
+                    expected: locked.checksum.clone(),
+                    found: package.checksum,
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Hashes the local modules of a workspace, as pinned by the lockfile.
+///
+/// Modules are hashed by path along with the source of the file they were loaded from, in order,
+/// so the hash only changes with the content of the package, not with where it is installed.
+pub fn sources_checksum(workspace: &Workspace) -> String {
+    let mut modules = workspace.package_local_modules().keys().collect::<Vec<_>>();
+    modules.sort();
+
+    let mut hasher = blake3::Hasher::new();
+    for module in modules {
+        hasher.update(module.as_bytes());
+        hasher.update(b"\0");
+        if let Some(file) = workspace.source_file(module) {
+            hasher.update(file.source.as_bytes());
+        }
+        hasher.update(b"\0");
+    }
+
+    hasher.finalize().to_hex().to_string()
+}
+
+/// Helper function to collect every dependency of a workspace, recursively.
+///
+/// Dependencies are collected every time they are found, so the same package found in two places
+/// is checked twice. Dependencies without a package, only built in memory, are skipped.
+fn locked_dependencies(workspace: &Workspace, packages: &mut Vec<LockedPackage>) {
+    let mut dependencies = workspace.dependencies().iter().collect::<Vec<_>>();
+    dependencies.sort_by_key(|(name, _)| *name);
+
+    for (name, dependency) in dependencies {
+        if let Some(package) = dependency.package() {
+            packages.push(LockedPackage {
+                name: name.clone(),
+                version: package.version.clone(),
+                checksum: sources_checksum(dependency),
+            });
+        }
+        locked_dependencies(dependency, packages);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lockfile::{LockedPackage, Lockfile, LockfileError};
+    use misty_parser::{Package, Workspace};
+    use semver::Version;
+    use std::fs;
+
+    #[test]
+    fn it_reads_lockfiles() {
+        let lockfile = Lockfile::new(vec![
+            LockedPackage {
+                name: "ml".to_string(),
+                version: Version::new(1, 2, 0),
+                checksum: "a1".to_string(),
+            },
+            LockedPackage {
+                name: "common_types".to_string(),
+                version: Version::new(0, 3, 1),
+                checksum: "b2".to_string(),
+            },
+        ]);
+        assert_eq!(lockfile.packages[0].name, "common_types");
+
+        let source = lockfile.to_toml().unwrap();
+        assert_eq!(Lockfile::from_toml(&source).unwrap(), lockfile);
+        assert_eq!(
+            lockfile.package("ml").map(|package| &package.version),
+            Some(&Version::new(1, 2, 0))
+        );
+
+        assert!(matches!(
+            Lockfile::from_toml("version = 2"),
+            Err(LockfileError::Version(2))
+        ));
+    }
+
+    #[test]
+    fn it_verifies_sources() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path();
+        fs::write(root.join("uuid.misty"), "schema Id {}").unwrap();
+        let workspace = |version: &str| {
+            let mut ids = Workspace::load_dir(root).unwrap();
+            ids.set_package(Package {
+                name: "ids".to_string(),
+                version: version.parse().unwrap(),
+            });
+            let mut workspace = Workspace::new();
+            workspace.add_dependency("ids", ids);
+            workspace
+        };
+
+        let lockfile = Lockfile::from_workspace(&workspace("1.0.0"));
+        assert_eq!(lockfile.packages.len(), 1);
+        assert_eq!(lockfile.packages[0].name, "ids");
+        lockfile.verify(&workspace("1.0.0")).unwrap();
+        assert!(matches!(
+            lockfile.verify(&workspace("1.0.1")),
+            Err(LockfileError::Outdated { name, .. }) if name == "ids"
+        ));
+        assert!(matches!(
+            Lockfile::default().verify(&workspace("1.0.0")),
+            Err(LockfileError::Unlocked(name)) if name == "ids"
+        ));
+
+        // Any change to the sources is caught, formatting included.
+        fs::write(root.join("uuid.misty"), "schema Id { }").unwrap();
+        assert!(matches!(
+            lockfile.verify(&workspace("1.0.0")),
+            Err(LockfileError::Checksum { name, .. }) if name == "ids"
+        ));
+    }
+}
@@ -0,0 +1,593 @@
+//! The manifest module reads `Misty.toml`, the manifest of a Misty project.
+//!
+//! The manifest declares the package owning the modules of the project, where its sources are, the
+//! packages it depends on and the code generators to run:
+//!
+//! ```toml
+//! [package]
+//! name = "jobs"
+//! version = "0.1.0"
+//! sources = ["schemas"]
+//!
+//! [dependencies]
+//! ml = "1.2"
+//! auth = { path = "../auth" }
+//!
+//! [generators.rust]
+//! output = "jobs-types/src"
+//! collections = "btree"
+//!
+//! [registry]
+//! path = "/srv/misty-registry"
+//!
+//! [lint]
+//! vec_of_option = false
+//! ```
+//!
+//! Generator tables are passed to their generator as-is, besides `output`, see
+//! [GeneratorConfig::options].
+//!
+//! Dependencies are packages with a manifest of their own. A dependency with a `path` is found in
+//! that directory, relative to the package depending on it. Any other dependency is vendored, and
+//! found in the `vendor/<name>` directory of the project root, shared by every package of the
+//! project, where they are installed from the registry, see [crate::registry].
+use crate::lint::{LintConfig, LintConfigError};
+use misty_parser::{LoadError, Package, Workspace, is_snake_case};
+use semver::{Version, VersionReq};
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub use misty_parser::MANIFEST_FILE;
+
+/// Directory of the vendored dependencies, at the root of a project.
+pub const VENDOR_DIR: &str = "vendor";
+
+/// Errors raised while reading a manifest.
+#[derive(Debug, thiserror::Error)]
+pub enum ManifestError {
+    #[error("Failed to read `{path}`: {source}")]
+    Read {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("Failed to write `{path}`: {source}")]
+    Write {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("The manifest is invalid: {0}")]
+    Toml(#[from] toml::de::Error),
+
+    #[error("The manifest can't be edited: {0}")]
+    Edit(#[from] toml_edit::TomlError),
+
+    #[error("The package name `{0}` is not a snake_case name")]
+    InvalidPackageName(String),
+
+    #[error("The dependency `{0}` has neither a version nor a path")]
+    InvalidDependency(String),
+
+    #[error("The options of the generator `{generator}` are invalid: {source}")]
+    GeneratorOptions {
+        generator: String,
+        source: toml::de::Error,
+    },
+
+    #[error(transparent)]
+    Lint(#[from] LintConfigError),
+
+    #[error("The dependency `{name}` is the package `{found}`")]
+    DependencyMismatch { name: String, found: String },
+
+    #[error("The dependency `{name}` requires `{requirement}`, but version {version} was found")]
+    DependencyVersion {
+        name: String,
+        requirement: VersionReq,
+        version: Version,
+    },
+
+    #[error("The dependency `{0}` depends on itself")]
+    DependencyCycle(String),
+}
+
+/// Errors raised while loading the workspace of a package and its dependencies.
+#[derive(Debug, thiserror::Error)]
+pub enum WorkspaceError {
+    #[error("Failed to load the package at `{}`: {error}", .root.display())]
+    Manifest {
+        root: PathBuf,
+        #[source]
+        error: Box<ManifestError>,
+    },
+
+    #[error("Failed to load {} Misty file(s)", .0.len())]
+    Load(Vec<LoadError>),
+}
+
+/// The manifest of a Misty project.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Manifest {
+    /// The package of the project.
+    pub package: PackageManifest,
+
+    /// Packages the project depends on, by name.
+    #[serde(default)]
+    pub dependencies: BTreeMap<String, Dependency>,
+
+    /// Code generators to run, by name, like `rust`.
+    #[serde(default)]
+    pub generators: BTreeMap<String, GeneratorConfig>,
+
+    /// The registry dependencies are installed from and packages published to.
+    pub registry: Option<RegistryConfig>,
+
+    /// Rules enabled in the linter.
+    #[serde(default)]
+    pub lint: LintConfig,
+}
+
+/// The `[registry]` table of a manifest.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RegistryConfig {
+    /// Directory of the registry, relative to the project root.
+    pub path: PathBuf,
+}
+
+/// The `[package]` table of a manifest.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PackageManifest {
+    /// Name of the package, in snake_case.
+    pub name: String,
+
+    /// Version of the package.
+    pub version: Version,
+
+    /// Directories containing the Misty files of the package, relative to the project root.
+    ///
+    /// Defaults to the project root itself.
+    #[serde(default = "default_sources")]
+    pub sources: Vec<PathBuf>,
+}
+
+/// A dependency of a package.
+///
+/// Dependencies are declared with a version requirement, `ml = "1.2"`, or a table with a version
+/// requirement and the path of a local package, `ml = { path = "../ml" }`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(from = "DependencyEntry")]
+pub struct Dependency {
+    /// Versions of the package accepted.
+    pub version: Option<VersionReq>,
+
+    /// Path of the package root, relative to the project root.
+    pub path: Option<PathBuf>,
+}
+
+/// Helper enum to read both forms of dependencies.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum DependencyEntry {
+    Version(VersionReq),
+    Table(DependencyTable),
+}
+
+/// Helper struct to read dependencies declared as tables.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DependencyTable {
+    version: Option<VersionReq>,
+    path: Option<PathBuf>,
+}
+
+impl Dependency {
+    /// Helper method to write the dependency in a manifest, in the shortest form possible.
+    fn to_toml(&self) -> toml_edit::Item {
+        match (&self.version, &self.path) {
+            (Some(version), None) => toml_edit::value(version.to_string()),
+            (version, path) => {
+                let mut table = toml_edit::InlineTable::new();
+                if let Some(version) = version {
+                    table.insert("version", version.to_string().into());
+                }
+                if let Some(path) = path {
+                    table.insert("path", path.to_string_lossy().as_ref().into());
+                }
+                toml_edit::value(table)
+            }
+        }
+    }
+}
+
+impl From<DependencyEntry> for Dependency {
+    fn from(entry: DependencyEntry) -> Self {
+        match entry {
+            DependencyEntry::Version(version) => Self {
+                version: Some(version),
+                path: None,
+            },
+            DependencyEntry::Table(DependencyTable { version, path }) => Self { version, path },
+        }
+    }
+}
+
+/// The settings of a code generator, from its `[generators.<name>]` table.
+#[derive(Debug, Clone, Deserialize)]
+pub struct GeneratorConfig {
+    /// Directory the code is generated in, relative to the project root.
+    pub output: PathBuf,
+
+    /// Every other setting of the table, read by [GeneratorConfig::options].
+    #[serde(flatten)]
+    pub options: toml::Table,
+}
+
+impl GeneratorConfig {
+    /// Reads the options of the generator, like `RustCodeGeneratorOptions`.
+    pub fn options<T: DeserializeOwned>(&self, generator: &str) -> Result<T, ManifestError> {
+        toml::Value::Table(self.options.clone())
+            .try_into()
+            .map_err(|source| ManifestError::GeneratorOptions {
+                generator: generator.to_string(),
+                source,
+            })
+    }
+}
+
+impl Manifest {
+    /// Reads a manifest from its source.
+    pub fn from_toml(source: &str) -> Result<Self, ManifestError> {
+        let manifest = toml::from_str::<Self>(source)?;
+        manifest.validate()?;
+
+        Ok(manifest)
+    }
+
+    /// Reads the manifest at the root of a project.
+    pub fn load(root: &Path) -> Result<Self, ManifestError> {
+        let path = root.join(MANIFEST_FILE);
+        let source =
+            fs::read_to_string(&path).map_err(|source| ManifestError::Read { path, source })?;
+
+        Self::from_toml(&source)
+    }
+
+    /// Adds a dependency to the manifest at the root of a project, or replaces it, keeping the
+    /// formatting and comments of the manifest.
+    ///
+    /// Returns the updated manifest.
+    pub fn add_dependency(
+        root: &Path,
+        name: &str,
+        dependency: &Dependency,
+    ) -> Result<Self, ManifestError> {
+        let path = root.join(MANIFEST_FILE);
+        let source = fs::read_to_string(&path).map_err(|source| ManifestError::Read {
+            path: path.clone(),
+            source,
+        })?;
+
+        let mut document = source.parse::<toml_edit::DocumentMut>()?;
+        let dependencies = document
+            .entry("dependencies")
+            .or_insert_with(toml_edit::table)
+            .as_table_like_mut()
+            .ok_or_else(|| ManifestError::InvalidDependency(name.to_string()))?;
+        dependencies.insert(name, dependency.to_toml());
+
+        let source = document.to_string();
+        let manifest = Self::from_toml(&source)?;
+        fs::write(&path, source).map_err(|source| ManifestError::Write { path, source })?;
+
+        Ok(manifest)
+    }
+
+    /// Checks the names of the package and its dependencies, and the lint configuration.
+    pub fn validate(&self) -> Result<(), ManifestError> {
+        if !is_snake_case(&self.package.name) {
+            return Err(ManifestError::InvalidPackageName(self.package.name.clone()));
+        }
+        for (name, dependency) in &self.dependencies {
+            if !is_snake_case(name) {
+                return Err(ManifestError::InvalidPackageName(name.clone()));
+            }
+            if dependency.version.is_none() && dependency.path.is_none() {
+                return Err(ManifestError::InvalidDependency(name.clone()));
+            }
+        }
+        self.lint.validate()?;
+
+        Ok(())
+    }
+
+    /// The package declared by the manifest.
+    pub fn package(&self) -> Package {
+        Package {
+            name: self.package.name.clone(),
+            version: self.package.version.clone(),
+        }
+    }
+
+    /// Loads the Misty files of every source root as the modules of the package, along with the
+    /// workspaces of its dependencies, recursively.
+    ///
+    /// Every source root of a package is loaded even if some fail, and every error found is
+    /// returned. Loading stops at the first package failing to load.
+    pub fn load_workspace(&self, root: &Path) -> Result<Workspace, WorkspaceError> {
+        let vendor_dir = root.join(VENDOR_DIR);
+        self.load_package(root, &vendor_dir, &mut Vec::new())
+    }
+
+    /// Loads the Misty files of every source root as the modules of the package, without its
+    /// dependencies.
+    pub fn load_sources(&self, root: &Path) -> Result<Workspace, WorkspaceError> {
+        let mut workspace = Workspace::new();
+        workspace.set_package(self.package());
+
+        let mut errors = Vec::new();
+        for source in &self.package.sources {
+            if let Err(source_errors) = workspace.add_dir(&root.join(source)) {
+                errors.extend(source_errors);
+            }
+        }
+        if !errors.is_empty() {
+            return Err(WorkspaceError::Load(errors));
+        }
+
+        Ok(workspace)
+    }
+
+    /// Helper method to load the workspace of a package, given the names of the packages depending
+    /// on it to detect cycles.
+    fn load_package(
+        &self,
+        root: &Path,
+        vendor_dir: &Path,
+        dependents: &mut Vec<String>,
+    ) -> Result<Workspace, WorkspaceError> {
+        let mut workspace = self.load_sources(root)?;
+
+        dependents.push(self.package.name.clone());
+        for (name, dependency) in &self.dependencies {
+            let dependency_root = match &dependency.path {
+                Some(path) => root.join(path),
+                None => vendor_dir.join(name),
+            };
+            let manifest_error = |error| WorkspaceError::Manifest {
+                root: dependency_root.clone(),
+                error: Box::new(error),
+            };
+
+            if dependents.contains(name) {
+                return Err(manifest_error(ManifestError::DependencyCycle(name.clone())));
+            }
+            let manifest = Manifest::load(&dependency_root).map_err(manifest_error)?;
+            manifest
+                .attest_dependency(name, dependency)
+                .map_err(manifest_error)?;
+
+            tracing::trace!(?name, ?dependency_root, "Loading a dependency");
+            let dependency_workspace =
+                manifest.load_package(&dependency_root, vendor_dir, dependents)?;
+            workspace.add_dependency(name, dependency_workspace);
+        }
+        dependents.pop();
+
+        Ok(workspace)
+    }
+
+    /// Helper method to check that this manifest is the one of a dependency.
+    fn attest_dependency(&self, name: &str, dependency: &Dependency) -> Result<(), ManifestError> {
+        if self.package.name != name {
+            return Err(ManifestError::DependencyMismatch {
+                name: name.to_string(),
+                found: self.package.name.clone(),
+            });
+        }
+        if let Some(requirement) = &dependency.version
+            && !requirement.matches(&self.package.version)
+        {
+            return Err(ManifestError::DependencyVersion {
+                name: name.to_string(),
+                requirement: requirement.clone(),
+                version: self.package.version.clone(),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// Helper function for the default source root, the project root.
+fn default_sources() -> Vec<PathBuf> {
+    vec![PathBuf::from(".")]
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use crate::lint::LintRule;
+    use crate::manifest::{
+        Dependency, MANIFEST_FILE, Manifest, ManifestError, VENDOR_DIR, WorkspaceError,
+    };
+    use semver::VersionReq;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    /// Helper function to write the manifest of a package to `dir`, creating it if needed.
+    ///
+    /// `extra` is appended to the manifest, like a `[dependencies]` table.
+    pub(crate) fn write_manifest(dir: &Path, name: &str, version: &str, extra: &str) {
+        fs::create_dir_all(dir).unwrap();
+        let manifest = format!("[package]\nname = \"{name}\"\nversion = \"{version}\"\n{extra}");
+        fs::write(dir.join(MANIFEST_FILE), manifest).unwrap();
+    }
+
+    #[test]
+    fn it_reads_manifests() {
+        let manifest = Manifest::from_toml(
+            r#"
+            [package]
+            name = "jobs"
+            version = "0.1.0"
+            sources = ["schemas"]
+
+            [dependencies]
+            ml = "1.2"
+            auth = { path = "../auth" }
+
+            [generators.rust]
+            output = "jobs-types/src"
+            collections = "btree"
+
+            [lint]
+            vec_of_option = false
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(manifest.package().to_string(), "jobs 0.1.0");
+        assert_eq!(manifest.package.sources, vec![PathBuf::from("schemas")]);
+        let ml = &manifest.dependencies["ml"];
+        assert_eq!(ml.version, Some(VersionReq::parse("1.2").unwrap()));
+        assert_eq!(ml.path, None);
+        assert_eq!(
+            manifest.dependencies["auth"].path,
+            Some(PathBuf::from("../auth"))
+        );
+        let rust = &manifest.generators["rust"];
+        assert_eq!(rust.output, PathBuf::from("jobs-types/src"));
+        assert_eq!(
+            rust.options
+                .get("collections")
+                .and_then(|value| value.as_str()),
+            Some("btree")
+        );
+        assert!(!manifest.lint.is_enabled(LintRule::VecOfOption));
+
+        let manifest =
+            Manifest::from_toml("[package]\nname = \"jobs\"\nversion = \"1.0.0\"").unwrap();
+        assert_eq!(manifest.package.sources, vec![PathBuf::from(".")]);
+
+        assert!(matches!(
+            Manifest::from_toml("[package]\nname = \"Jobs\"\nversion = \"1.0.0\""),
+            Err(ManifestError::InvalidPackageName(name)) if name == "Jobs"
+        ));
+        assert!(matches!(
+            Manifest::from_toml("[package]\nname = \"jobs\"\nversion = \"1.0\""),
+            Err(ManifestError::Toml(_))
+        ));
+        assert!(matches!(
+            Manifest::from_toml(
+                "[package]\nname = \"jobs\"\nversion = \"1.0.0\"\n[dependencies]\nml = {}"
+            ),
+            Err(ManifestError::InvalidDependency(name)) if name == "ml"
+        ));
+    }
+
+    #[test]
+    fn it_loads_dependencies() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path();
+        let jobs = root.join("jobs");
+        write_manifest(
+            &jobs,
+            "jobs",
+            "0.1.0",
+            "[dependencies]\ncommon_types = { path = \"../common\" }",
+        );
+        fs::write(
+            jobs.join("jobs.misty"),
+            "import common_types.users.{User};\nschema Job { owner: User; }",
+        )
+        .unwrap();
+        let common = root.join("common");
+        write_manifest(
+            &common,
+            "common_types",
+            "1.2.0",
+            "[dependencies]\nids = \"1\"",
+        );
+        fs::write(
+            common.join("users.misty"),
+            "import ids.uuid;\nschema User { id: ids.uuid.Id; }",
+        )
+        .unwrap();
+        let ids = jobs.join(VENDOR_DIR).join("ids");
+        write_manifest(&ids, "ids", "1.0.3", "");
+        fs::write(ids.join("uuid.misty"), "schema Id {}").unwrap();
+
+        // Vendored dependencies are found in the vendor directory of the project root.
+        let manifest = Manifest::load(&jobs).unwrap();
+        let mut workspace = manifest.load_workspace(&jobs).unwrap();
+        workspace.validate_all().unwrap();
+        assert_eq!(workspace.package().unwrap().name, "jobs");
+        let common_types = &workspace.dependencies()["common_types"];
+        assert_eq!(
+            common_types.package().unwrap().to_string(),
+            "common_types 1.2.0"
+        );
+        assert!(common_types.dependencies().contains_key("ids"));
+
+        write_manifest(&ids, "ids", "2.0.0", "");
+        let Err(WorkspaceError::Manifest { error, .. }) = manifest.load_workspace(&jobs) else {
+            panic!("expected a version mismatch");
+        };
+        assert!(matches!(*error, ManifestError::DependencyVersion { name, .. } if name == "ids"));
+
+        write_manifest(
+            &ids,
+            "ids",
+            "1.0.3",
+            "[dependencies]\njobs = { path = \"../..\" }",
+        );
+        let Err(WorkspaceError::Manifest { error, .. }) = manifest.load_workspace(&jobs) else {
+            panic!("expected a dependency cycle");
+        };
+        assert!(matches!(*error, ManifestError::DependencyCycle(name) if name == "jobs"));
+    }
+
+    #[test]
+    fn it_adds_dependencies() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path();
+        let source = "# Jobs\n[package]\nname = \"jobs\"\nversion = \"0.1.0\"\n";
+        fs::write(root.join(MANIFEST_FILE), source).unwrap();
+
+        let ids = Dependency {
+            version: Some("^1.2".parse().unwrap()),
+            path: None,
+        };
+        Manifest::add_dependency(root, "ids", &ids).unwrap();
+        let common_types = Dependency {
+            version: None,
+            path: Some(PathBuf::from("../common")),
+        };
+        let manifest = Manifest::add_dependency(root, "common_types", &common_types).unwrap();
+        assert_eq!(manifest.dependencies.len(), 2);
+
+        // The manifest keeps its formatting, dependencies are written in their shortest form.
+        assert_eq!(
+            fs::read_to_string(root.join(MANIFEST_FILE)).unwrap(),
+            format!(
+                "{source}\n[dependencies]\nids = \"^1.2\"\n\
+                 common_types = {{ path = \"../common\" }}\n"
+            )
+        );
+
+        let invalid = Dependency {
+            version: None,
+            path: None,
+        };
+        assert!(matches!(
+            Manifest::add_dependency(root, "ml", &invalid),
+            Err(ManifestError::InvalidDependency(name)) if name == "ml"
+        ));
+    }
+}
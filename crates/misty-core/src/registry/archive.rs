@@ -0,0 +1,86 @@
+use crate::manifest::{MANIFEST_FILE, Manifest};
+use crate::registry::RegistryError;
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use misty_parser::find_misty_files;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Packs the manifest and the Misty files of a package into a gzipped tarball.
+///
+/// Archives only depend on the content of the files: they are sorted, and their metadata is left
+/// out, so packing the same package twice gives the same archive, with the same hash. The path of
+/// dependencies and the registry are dropped from the manifest, they only make sense on the
+/// machine of the publisher.
+pub fn pack(root: &Path, manifest: &Manifest) -> Result<Vec<u8>, RegistryError> {
+    let manifest_path = root.join(MANIFEST_FILE);
+    let source = fs::read_to_string(&manifest_path).map_err(|source| RegistryError::Read {
+        path: manifest_path,
+        source,
+    })?;
+    let mut files = vec![(PathBuf::from(MANIFEST_FILE), published_manifest(&source)?)];
+
+    for source_root in &manifest.package.sources {
+        for path in find_misty_files(&root.join(source_root))? {
+            let content = fs::read(&path).map_err(|source| RegistryError::Read {
+                path: path.clone(),
+                source,
+            })?;
+            let relative = path.strip_prefix(root).unwrap_or(&path).to_path_buf();
+            files.push((relative, content));
+        }
+    }
+    files.sort();
+
+    let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+    for (path, content) in &files {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(content.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(0);
+        builder
+            .append_data(&mut header, path, content.as_slice())
+            .map_err(RegistryError::Archive)?;
+    }
+
+    builder
+        .into_inner()
+        .and_then(|encoder| encoder.finish())
+        .map_err(RegistryError::Archive)
+}
+
+/// Unpacks an archive made by [pack] into a directory.
+///
+/// Entries leading outside of the directory are skipped.
+pub fn unpack(archive: &[u8], dir: &Path) -> Result<(), RegistryError> {
+    fs::create_dir_all(dir).map_err(|source| RegistryError::Write {
+        path: dir.to_path_buf(),
+        source,
+    })?;
+
+    tar::Archive::new(GzDecoder::new(archive))
+        .unpack(dir)
+        .map_err(RegistryError::Archive)
+}
+
+/// Helper function to drop what only makes sense on the machine of the publisher from a manifest.
+fn published_manifest(source: &str) -> Result<Vec<u8>, RegistryError> {
+    let mut document = source
+        .parse::<toml_edit::DocumentMut>()
+        .map_err(|error| RegistryError::Manifest(Box::new(error.into())))?;
+    document.remove("registry");
+
+    if let Some(dependencies) = document
+        .get_mut("dependencies")
+        .and_then(|dependencies| dependencies.as_table_like_mut())
+    {
+        for (_, dependency) in dependencies.iter_mut() {
+            if let Some(dependency) = dependency.as_table_like_mut() {
+                dependency.remove("path");
+            }
+        }
+    }
+
+    Ok(document.to_string().into_bytes())
+}
@@ -0,0 +1,131 @@
+use crate::registry::{IndexEntry, Registry, RegistryError};
+use misty_parser::is_snake_case;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Name of the directory holding the index of a registry.
+const INDEX_DIR: &str = "index";
+
+/// Name of the directory holding the archives of a registry.
+const ARCHIVES_DIR: &str = "archives";
+
+/// A registry stored in a directory.
+///
+/// The index of a package is a file in `index/`, named after the package, listing one
+/// [IndexEntry] per line as JSON. Archives are stored in `archives/<name>/<version>.tar.gz`. Both
+/// directories are created by the first publication.
+#[derive(Debug, Clone)]
+pub struct DirectoryRegistry {
+    /// Root directory of the registry.
+    root: PathBuf,
+}
+
+impl DirectoryRegistry {
+    /// Creates a registry stored in `root`.
+    pub fn new(root: &Path) -> Self {
+        Self {
+            root: root.to_path_buf(),
+        }
+    }
+
+    /// Helper method for the path of the index of a package.
+    ///
+    /// Names that are not package names are rejected, they could lead outside of the registry.
+    fn index_path(&self, name: &str) -> Result<PathBuf, RegistryError> {
+        if !is_snake_case(name) {
+            return Err(RegistryError::InvalidPackageName(name.to_string()));
+        }
+
+        Ok(self.root.join(INDEX_DIR).join(name))
+    }
+
+    /// Helper method for the path of the archive of a version of a package.
+    fn archive_path(&self, entry: &IndexEntry) -> Result<PathBuf, RegistryError> {
+        if !is_snake_case(&entry.name) {
+            return Err(RegistryError::InvalidPackageName(entry.name.clone()));
+        }
+
+        Ok(self
+            .root
+            .join(ARCHIVES_DIR)
+            .join(&entry.name)
+            .join(format!("{}.tar.gz", entry.version)))
+    }
+}
+
+impl Registry for DirectoryRegistry {
+    fn versions(&self, name: &str) -> Result<Vec<IndexEntry>, RegistryError> {
+        let path = self.index_path(name)?;
+        if !path.exists() {
+            return Err(RegistryError::PackageNotFound(name.to_string()));
+        }
+        let index = fs::read_to_string(&path).map_err(|source| RegistryError::Read {
+            path: path.clone(),
+            source,
+        })?;
+
+        index
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                serde_json::from_str(line).map_err(|source| RegistryError::Index {
+                    path: path.clone(),
+                    source,
+                })
+            })
+            .collect()
+    }
+
+    fn download(&self, entry: &IndexEntry) -> Result<Vec<u8>, RegistryError> {
+        let path = self.archive_path(entry)?;
+        fs::read(&path).map_err(|source| RegistryError::Read { path, source })
+    }
+
+    #[tracing::instrument(
+        skip(self, archive),
+        fields(name = %entry.name, version = %entry.version)
+    )]
+    fn publish(&self, entry: &IndexEntry, archive: &[u8]) -> Result<(), RegistryError> {
+        let published = match self.versions(&entry.name) {
+            Ok(versions) => versions,
+            Err(RegistryError::PackageNotFound(_)) => Vec::new(),
+            Err(error) => return Err(error),
+        };
+        if published
+            .iter()
+            .any(|published| published.version == entry.version)
+        {
+            return Err(RegistryError::AlreadyPublished {
+                name: entry.name.clone(),
+                version: entry.version.clone(),
+            });
+        }
+
+        // The archive is written first, so the index never lists a version without its archive.
+        let archive_path = self.archive_path(entry)?;
+        let write_error = |path: &Path| {
+            let path = path.to_path_buf();
+            move |source| RegistryError::Write { path, source }
+        };
+        if let Some(parent) = archive_path.parent() {
+            fs::create_dir_all(parent).map_err(write_error(parent))?;
+        }
+        fs::write(&archive_path, archive).map_err(write_error(&archive_path))?;
+
+        let index_path = self.index_path(&entry.name)?;
+        if let Some(parent) = index_path.parent() {
+            fs::create_dir_all(parent).map_err(write_error(parent))?;
+        }
+        let line = serde_json::to_string(entry).map_err(|source| RegistryError::Index {
+            path: index_path.clone(),
+            source,
+        })?;
+        fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&index_path)
+            .and_then(|mut index| writeln!(index, "{line}"))
+            .map_err(write_error(&index_path))
+    }
+}
@@ -0,0 +1,450 @@
+//! The registry module contains the package registry, where packages are published to and
+//! dependencies installed from.
+//!
+//! A registry is anything implementing [Registry]: it lists the published versions of a package
+//! from its index, serves their archives and accepts new ones. [DirectoryRegistry] is a registry
+//! stored in a directory, which can be shared on a network drive or synced by any other mean to
+//! run an internal registry without a live service.
+//!
+//! Archives are gzipped tarballs of the manifest and the Misty files of a package, identified by
+//! their blake3 hash. Every archive installed is checked against the hash recorded in the index
+//! when it was published, and the sources it contains against the lockfile, see [install].
+//!
+//! Dependencies without a `path` are registry dependencies: [resolve] picks the newest version of
+//! each one matching every requirement found in the packages depending on it, preferring the
+//! versions pinned by the lockfile of the project.
+mod archive;
+mod directory;
+mod resolver;
+
+pub use archive::{pack, unpack};
+pub use directory::DirectoryRegistry;
+pub use resolver::resolve;
+
+use crate::lockfile::{Lockfile, LockfileError, sources_checksum};
+use crate::manifest::{Dependency, Manifest, ManifestError, VENDOR_DIR};
+use misty_parser::LoadError;
+use semver::{Version, VersionReq};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Errors raised while using a registry.
+#[derive(Debug, thiserror::Error)]
+pub enum RegistryError {
+    #[error("Failed to read `{path}`: {source}")]
+    Read {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("Failed to write `{path}`: {source}")]
+    Write {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("The index of `{path}` is invalid: {source}")]
+    Index {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+
+    #[error("The package `{0}` was not found in the registry")]
+    PackageNotFound(String),
+
+    #[error("The package name `{0}` is not a snake_case name")]
+    InvalidPackageName(String),
+
+    #[error("No version of `{name}` matches `{requirements}`")]
+    NoMatchingVersion { name: String, requirements: String },
+
+    #[error("The versions of `{0}` keep changing while resolving dependencies")]
+    Unresolvable(String),
+
+    #[error("The package `{name}` {version} is already published")]
+    AlreadyPublished { name: String, version: Version },
+
+    #[error("The dependency `{0}` has no version, it can't be published")]
+    UnversionedDependency(String),
+
+    #[error("The archive of `{name}` {version} has the hash {found}, expected {expected}")]
+    Checksum {
+        name: String,
+        version: Version,
+        expected: String,
+        found: String,
+    },
+
+    #[error("The archive is invalid: {0}")]
+    Archive(std::io::Error),
+
+    #[error(transparent)]
+    Load(#[from] LoadError),
+
+    #[error(transparent)]
+    Manifest(#[from] Box<ManifestError>),
+
+    #[error(transparent)]
+    Lockfile(#[from] LockfileError),
+}
+
+impl From<ManifestError> for RegistryError {
+    fn from(error: ManifestError) -> Self {
+        Self::Manifest(Box::new(error))
+    }
+}
+
+/// A published version of a package, as listed by the index of a registry.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexEntry {
+    /// Name of the package.
+    pub name: String,
+
+    /// Version of the package.
+    pub version: Version,
+
+    /// blake3 hash of the archive of the package, in hexadecimal.
+    pub checksum: String,
+
+    /// Requirements of the registry dependencies of the package, by name.
+    #[serde(default)]
+    pub dependencies: BTreeMap<String, VersionReq>,
+}
+
+/// Main trait to define a common interface for all registries supported by Misty.
+pub trait Registry {
+    /// Lists every published version of a package, oldest first.
+    ///
+    /// Fails with [RegistryError::PackageNotFound] when the package was never published.
+    fn versions(&self, name: &str) -> Result<Vec<IndexEntry>, RegistryError>;
+
+    /// Gets the archive of a published version of a package, as published.
+    fn download(&self, entry: &IndexEntry) -> Result<Vec<u8>, RegistryError>;
+
+    /// Publishes the archive of a new version of a package.
+    ///
+    /// Published versions can't be replaced, they fail with [RegistryError::AlreadyPublished].
+    fn publish(&self, entry: &IndexEntry, archive: &[u8]) -> Result<(), RegistryError>;
+}
+
+/// Hashes an archive, as recorded in the index.
+pub fn checksum(archive: &[u8]) -> String {
+    blake3::hash(archive).to_hex().to_string()
+}
+
+/// Publishes the package at `root` to a registry.
+///
+/// Every dependency must have a version requirement: path dependencies are published as registry
+/// dependencies, and their path is dropped from the published manifest.
+pub fn publish(
+    registry: &dyn Registry,
+    root: &Path,
+    manifest: &Manifest,
+) -> Result<IndexEntry, RegistryError> {
+    let mut dependencies = BTreeMap::new();
+    for (name, dependency) in &manifest.dependencies {
+        let Some(version) = &dependency.version else {
+            return Err(RegistryError::UnversionedDependency(name.clone()));
+        };
+        dependencies.insert(name.clone(), version.clone());
+    }
+
+    let archive = pack(root, manifest)?;
+    let entry = IndexEntry {
+        name: manifest.package.name.clone(),
+        version: manifest.package.version.clone(),
+        checksum: checksum(&archive),
+        dependencies,
+    };
+    registry.publish(&entry, &archive)?;
+
+    Ok(entry)
+}
+
+/// Installs a package from a registry in the vendor directory of a project, replacing any other
+/// version of the package.
+///
+/// The archive is checked against the hash recorded in the index before being unpacked, and the
+/// sources unpacked against the lockfile when it pins the same version: the installed package is
+/// only replaced once both match. Packages already installed at the same version are left as is,
+/// unless their sources differ from the lockfile.
+pub fn install(
+    registry: &dyn Registry,
+    entry: &IndexEntry,
+    vendor_dir: &Path,
+    lockfile: Option<&Lockfile>,
+) -> Result<(), RegistryError> {
+    let package_dir = vendor_dir.join(&entry.name);
+    let locked = lockfile
+        .and_then(|lockfile| lockfile.package(&entry.name))
+        .filter(|locked| locked.version == entry.version);
+    let installed = installed_checksum(&package_dir, entry);
+    if installed
+        .as_ref()
+        .is_some_and(|installed| locked.is_none_or(|locked| &locked.checksum == installed))
+    {
+        return Ok(());
+    }
+
+    let archive = registry.download(entry)?;
+    let found = checksum(&archive);
+    if found != entry.checksum {
+        return Err(RegistryError::Checksum {
+            name: entry.name.clone(),
+            version: entry.version.clone(),
+            expected: entry.checksum.clone(),
+            found,
+        });
+    }
+
+    // The package is unpacked next to the installed one, and only replaces it once checked.
+    let staging_dir = vendor_dir.join(format!(".{}.tmp", entry.name));
+    remove_dir(&staging_dir)?;
+    tracing::debug!(?entry.name, %entry.version, "Installing a package");
+    let staged = unpack(&archive, &staging_dir).and_then(|()| match locked {
+        Some(locked) => {
+            let found = installed_checksum(&staging_dir, entry).unwrap_or_default();
+            if found != locked.checksum {
+                return Err(LockfileError::Checksum {
+                    name: entry.name.clone(),
+                    version: entry.version.clone(),
+                    expected: locked.checksum.clone(),
+                    found,
+                }
+                .into());
+            }
+            Ok(())
+        }
+        None => Ok(()),
+    });
+    if let Err(error) = staged {
+        let _ = fs::remove_dir_all(&staging_dir);
+        return Err(error);
+    }
+
+    remove_dir(&package_dir)?;
+    fs::rename(&staging_dir, &package_dir).map_err(|source| RegistryError::Write {
+        path: package_dir,
+        source,
+    })
+}
+
+/// Resolves the registry dependencies of the project at `root` and installs them in its vendor
+/// directory.
+///
+/// Versions pinned by the lockfile of the project are kept as long as they match the requirements
+/// of the project. The lockfile is not updated, it pins the sources of every dependency: see
+/// [Lockfile::from_workspace] once the workspace of the project is loaded.
+pub fn install_dependencies(
+    registry: &dyn Registry,
+    root: &Path,
+    manifest: &Manifest,
+) -> Result<Vec<IndexEntry>, RegistryError> {
+    let lockfile = Lockfile::load(root)?;
+    let entries = resolve(root, manifest, registry, lockfile.as_ref())?;
+
+    let vendor_dir = root.join(VENDOR_DIR);
+    for entry in &entries {
+        install(registry, entry, &vendor_dir, lockfile.as_ref())?;
+    }
+
+    Ok(entries)
+}
+
+/// Helper function to remove a directory, if it exists.
+fn remove_dir(dir: &Path) -> Result<(), RegistryError> {
+    if !dir.exists() {
+        return Ok(());
+    }
+
+    fs::remove_dir_all(dir).map_err(|source| RegistryError::Write {
+        path: dir.to_path_buf(),
+        source,
+    })
+}
+
+/// Helper function to hash the sources of a package installed at the version of an index entry, if
+/// it is.
+fn installed_checksum(package_dir: &Path, entry: &IndexEntry) -> Option<String> {
+    let manifest = Manifest::load(package_dir)
+        .ok()
+        .filter(|manifest| manifest.package.version == entry.version)?;
+    let workspace = manifest.load_sources(package_dir).ok()?;
+
+    Some(sources_checksum(&workspace))
+}
+
+/// Helper function to list the registry dependencies of a manifest.
+fn registry_dependencies(manifest: &Manifest) -> impl Iterator<Item = (&String, &VersionReq)> {
+    manifest
+        .dependencies
+        .iter()
+        .filter_map(|(name, dependency)| match dependency {
+            Dependency {
+                version: Some(version),
+                path: None,
+            } => Some((name, version)),
+            _ => None,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lockfile::{LockedPackage, Lockfile, LockfileError};
+    use crate::manifest::tests::write_manifest;
+    use crate::manifest::{Manifest, VENDOR_DIR};
+    use crate::registry::{
+        DirectoryRegistry, Registry, RegistryError, checksum, install, install_dependencies,
+        installed_checksum, pack, publish, resolve,
+    };
+    use semver::Version;
+    use std::fs;
+    use std::path::Path;
+
+    #[test]
+    fn it_publishes_and_installs_packages() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path();
+        let registry = DirectoryRegistry::new(&root.join("registry"));
+        let package = |name: &str, version: &str, extra: &str| {
+            let dir = root.join(name);
+            write_manifest(&dir, name, version, extra);
+            dir
+        };
+
+        let ids = package("ids", "1.0.0", "");
+        fs::write(ids.join("uuid.misty"), "schema Id {}").unwrap();
+        for version in ["1.0.0", "1.1.0", "2.0.0"] {
+            let ids = package("ids", version, "");
+            publish(&registry, &ids, &Manifest::load(&ids).unwrap()).unwrap();
+        }
+        let manifest = Manifest::load(&ids).unwrap();
+        assert!(matches!(
+            publish(&registry, &ids, &manifest),
+            Err(RegistryError::AlreadyPublished { name, .. }) if name == "ids"
+        ));
+        // Names that are not package names never lead outside of the registry.
+        assert!(matches!(
+            registry.versions("../registry/index/ids"),
+            Err(RegistryError::InvalidPackageName(_))
+        ));
+        // Archives only depend on the content of the package.
+        assert_eq!(
+            pack(&ids, &manifest).unwrap(),
+            pack(&ids, &manifest).unwrap()
+        );
+
+        let common = package(
+            "common_types",
+            "0.1.0",
+            "[dependencies]\nids = { version = \"1\", path = \"../ids\" }",
+        );
+        fs::write(
+            common.join("users.misty"),
+            "import ids.uuid;\nschema User { id: ids.uuid.Id; }",
+        )
+        .unwrap();
+        publish(&registry, &common, &Manifest::load(&common).unwrap()).unwrap();
+
+        let jobs = package(
+            "jobs",
+            "0.1.0",
+            "[dependencies]\ncommon_types = \"0.1\"\nids = \"1.0\"",
+        );
+        fs::write(
+            jobs.join("jobs.misty"),
+            "import common_types.users.{User};\nschema Job { owner: User; }",
+        )
+        .unwrap();
+        let manifest = Manifest::load(&jobs).unwrap();
+
+        // The newest version matching every requirement is picked, unless the lockfile pins one.
+        let versions = |lockfile: Option<&Lockfile>| {
+            resolve(&jobs, &manifest, &registry, lockfile)
+                .unwrap()
+                .into_iter()
+                .map(|entry| format!("{} {}", entry.name, entry.version))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(versions(None), vec!["common_types 0.1.0", "ids 1.1.0"]);
+        let mut lockfile = Lockfile::default();
+        lockfile.packages.push(LockedPackage {
+            name: "ids".to_string(),
+            version: Version::new(1, 0, 0),
+            checksum: String::new(),
+        });
+        assert_eq!(
+            versions(Some(&lockfile)),
+            vec!["common_types 0.1.0", "ids 1.0.0"]
+        );
+
+        // Installed packages are loaded as vendored dependencies.
+        install_dependencies(&registry, &jobs, &manifest).unwrap();
+        let mut workspace = manifest.load_workspace(&jobs).unwrap();
+        workspace.validate_all().unwrap();
+
+        // Installed sources differing from the lockfile are installed again.
+        let lockfile = Lockfile::from_workspace(&workspace);
+        lockfile.save(&jobs).unwrap();
+        let uuid = jobs.join(VENDOR_DIR).join("ids").join("uuid.misty");
+        fs::write(&uuid, "schema Id { value: u8; }").unwrap();
+        install_dependencies(&registry, &jobs, &manifest).unwrap();
+        let workspace = manifest.load_workspace(&jobs).unwrap();
+        lockfile.verify(&workspace).unwrap();
+
+        let mut entry = registry.versions("ids").unwrap().remove(0);
+        entry.checksum = checksum(b"tampered");
+        assert!(matches!(
+            install(&registry, &entry, &root.join("other").join(VENDOR_DIR), None),
+            Err(RegistryError::Checksum { name, .. }) if name == "ids"
+        ));
+    }
+    #[test]
+    fn it_keeps_installed_packages_on_tampered_archives() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path();
+        let package = |dir: &Path, source: &str| {
+            write_manifest(dir, "ids", "1.0.0", "");
+            fs::write(dir.join("uuid.misty"), source).unwrap();
+        };
+
+        let registry = DirectoryRegistry::new(&root.join("registry"));
+        let ids = root.join("ids");
+        package(&ids, "schema Id {}");
+        let entry = publish(&registry, &ids, &Manifest::load(&ids).unwrap()).unwrap();
+
+        // The same version is published with other sources to another registry, as a compromised
+        // mirror would: the archive matches its own index, but not the lockfile.
+        let mirror = DirectoryRegistry::new(&root.join("mirror"));
+        let tampered = root.join("tampered");
+        package(&tampered, "schema Id { value: u8; }");
+        let tampered = publish(&mirror, &tampered, &Manifest::load(&tampered).unwrap()).unwrap();
+
+        let vendor_dir = root.join("jobs").join(VENDOR_DIR);
+        install(&registry, &entry, &vendor_dir, None).unwrap();
+        let installed = vendor_dir.join("ids");
+        let lockfile = Lockfile::new(vec![LockedPackage {
+            name: "ids".to_string(),
+            version: entry.version.clone(),
+            checksum: installed_checksum(&installed, &entry).unwrap(),
+        }]);
+
+        // Local changes to the installed package lead to installing it again, from the mirror.
+        let uuid = installed.join("uuid.misty");
+        fs::write(&uuid, "schema Id { local: u8; }").unwrap();
+        assert!(matches!(
+            install(&mirror, &tampered, &vendor_dir, Some(&lockfile)),
+            Err(RegistryError::Lockfile(LockfileError::Checksum { name, .. })) if name == "ids"
+        ));
+        assert_eq!(
+            fs::read_to_string(&uuid).unwrap(),
+            "schema Id { local: u8; }"
+        );
+        assert!(!vendor_dir.join(".ids.tmp").exists());
+
+        install(&registry, &entry, &vendor_dir, Some(&lockfile)).unwrap();
+        assert_eq!(fs::read_to_string(&uuid).unwrap(), "schema Id {}");
+    }
+}
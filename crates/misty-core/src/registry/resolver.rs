@@ -0,0 +1,131 @@
+use crate::lockfile::Lockfile;
+use crate::manifest::Manifest;
+use crate::registry::{IndexEntry, Registry, RegistryError, registry_dependencies};
+use semver::VersionReq;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+
+/// Maximum number of passes before giving up on resolving dependencies.
+const MAX_PASSES: usize = 64;
+
+/// Resolves the registry dependencies of the package at `root`, and of its path dependencies.
+///
+/// Every package gets a single version: the version pinned by `lockfile` if it still matches
+/// every requirement, the newest matching version otherwise. Requirements come from the packages
+/// depending on the package, so choosing a version can change the requirements of others: passes
+/// are repeated until the versions stop changing.
+///
+/// Packages are returned sorted by name.
+#[tracing::instrument(skip(manifest, registry, lockfile))]
+pub fn resolve(
+    root: &Path,
+    manifest: &Manifest,
+    registry: &dyn Registry,
+    lockfile: Option<&Lockfile>,
+) -> Result<Vec<IndexEntry>, RegistryError> {
+    let mut requirements = Vec::new();
+    collect_requirements(root, manifest, &mut HashSet::new(), &mut requirements)?;
+
+    let mut versions = HashMap::new();
+    let mut selected = BTreeMap::<String, IndexEntry>::new();
+    for _ in 0..MAX_PASSES {
+        // Requirements of the packages selected by the previous pass, on top of the local ones.
+        let mut pass_requirements = BTreeMap::<&str, Vec<&VersionReq>>::new();
+        let selected_requirements = selected
+            .values()
+            .flat_map(|entry| &entry.dependencies)
+            .map(|(name, requirement)| (name.as_str(), requirement));
+        for (name, requirement) in requirements
+            .iter()
+            .map(|(name, requirement)| (name.as_str(), requirement))
+            .chain(selected_requirements)
+        {
+            pass_requirements.entry(name).or_default().push(requirement);
+        }
+
+        let mut pass = BTreeMap::new();
+        for (name, requirements) in pass_requirements {
+            if !versions.contains_key(name) {
+                versions.insert(name.to_string(), registry.versions(name)?);
+            }
+            let entry = select(name, &versions[name], &requirements, lockfile)?;
+            pass.insert(name.to_string(), entry.clone());
+        }
+
+        if pass == selected {
+            return Ok(selected.into_values().collect());
+        }
+        selected = pass;
+    }
+
+    let name = selected.into_keys().next().unwrap_or_default();
+    Err(RegistryError::Unresolvable(name))
+}
+
+/// Helper function to collect the registry requirements of a package and its path dependencies.
+///
+/// Packages already visited are skipped, so path dependencies depending on each other are not
+/// followed forever.
+fn collect_requirements(
+    root: &Path,
+    manifest: &Manifest,
+    visited: &mut HashSet<String>,
+    requirements: &mut Vec<(String, VersionReq)>,
+) -> Result<(), RegistryError> {
+    if !visited.insert(manifest.package.name.clone()) {
+        return Ok(());
+    }
+
+    requirements.extend(
+        registry_dependencies(manifest).map(|(name, version)| (name.clone(), version.clone())),
+    );
+    for dependency in manifest.dependencies.values() {
+        if let Some(path) = &dependency.path {
+            let dependency_root = root.join(path);
+            let dependency_manifest = Manifest::load(&dependency_root)?;
+            collect_requirements(
+                &dependency_root,
+                &dependency_manifest,
+                visited,
+                requirements,
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Helper function to select the version of a package matching every requirement.
+fn select<'a>(
+    name: &str,
+    versions: &'a [IndexEntry],
+    requirements: &[&VersionReq],
+    lockfile: Option<&Lockfile>,
+) -> Result<&'a IndexEntry, RegistryError> {
+    let candidates = versions
+        .iter()
+        .filter(|entry| {
+            requirements
+                .iter()
+                .all(|requirement| requirement.matches(&entry.version))
+        })
+        .collect::<Vec<_>>();
+
+    let locked = lockfile
+        .and_then(|lockfile| lockfile.package(name))
+        .map(|locked| &locked.version);
+    let found = candidates
+        .iter()
+        .find(|entry| Some(&entry.version) == locked)
+        .or_else(|| candidates.iter().max_by_key(|entry| &entry.version))
+        .copied();
+
+    found.ok_or_else(|| RegistryError::NoMatchingVersion {
+        name: name.to_string(),
+        requirements: requirements
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", "),
+    })
+}
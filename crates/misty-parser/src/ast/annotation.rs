@@ -0,0 +1,70 @@
+use crate::ParserError;
+use crate::ast::{parse_literal, span_of};
+use crate::pest_parser::Rule;
+use misty_ast::{Annotation, AnnotationArgument};
+use pest::iterators::{Pair, Pairs};
+
+/// Parses the annotations placed before a definition or a member.
+///
+/// Annotations are consumed from the start of `pairs`, which is left at the first pair following
+/// them.
+pub fn parse_annotations(pairs: &mut Pairs<Rule>) -> Result<Vec<Annotation>, ParserError> {
+    let mut annotations = Vec::new();
+    while let Some(pair) = pairs
+        .peek()
+        .filter(|pair| pair.as_rule() == Rule::annotation)
+    {
+        pairs.next();
+        annotations.push(parse_annotation(pair)?);
+    }
+
+    Ok(annotations)
+}
+
+/// Parses a single Annotation.
+#[tracing::instrument(skip(pair))]
+fn parse_annotation(pair: Pair<Rule>) -> Result<Annotation, ParserError> {
+    let span = span_of(&pair);
+    let mut inner = pair.into_inner();
+
+    // Extract the name of this Annotation.
+    let name = inner
+        .next()
+        .ok_or(ParserError::AnnotationName(span))
+        .inspect_err(|error| tracing::debug!(?error, "Failed to parse the Annotation name"))?
+        .as_str()
+        .to_string();
+
+    // Parse the arguments of this Annotation, named ones start with their key.
+    let mut arguments = Vec::new();
+    for argument_pair in inner {
+        let argument_span = span_of(&argument_pair);
+        let mut argument_inner = argument_pair.into_inner();
+
+        let key = argument_inner
+            .peek()
+            .filter(|pair| pair.as_rule() == Rule::ident_snake)
+            .map(|pair| pair.as_str().to_string());
+        if key.is_some() {
+            argument_inner.next();
+        }
+
+        let value_pair = argument_inner
+            .next()
+            .ok_or(ParserError::Literal(argument_span))
+            .inspect_err(|error| tracing::debug!(?error, "Failed to parse the Argument value"))?;
+        let value = parse_literal(value_pair)?;
+
+        arguments.push(AnnotationArgument {
+            key,
+            value,
+            span: argument_span,
+        });
+    }
+
+    Ok(Annotation {
+        name,
+        arguments,
+        span,
+    })
+}
@@ -1,16 +1,19 @@
 use crate::ParserError;
+use crate::ast::span_of;
 use crate::pest_parser::Rule;
-use misty_ast::DataType;
+use misty_ast::{ContainerType, DataType};
 use pest::iterators::Pair;
 
 /// Parses a DataType component.
 #[tracing::instrument(skip(pair))]
 pub fn parse_data_type(pair: Pair<Rule>) -> Result<DataType, ParserError> {
+    let span = span_of(&pair);
+
     // Extract the inner token for the DataType definition.
     let data_type_pair = pair
         .into_inner()
         .next()
-        .ok_or(ParserError::DataType)
+        .ok_or(ParserError::DataType(span))
         .inspect_err(|error| tracing::debug!(?error, "Failed to parse the DataType"))?;
 
     // Parse the token as a Rule to match the proper type.
@@ -24,38 +27,77 @@ pub fn parse_data_type(pair: Pair<Rule>) -> Result<DataType, ParserError> {
 
         // Parsing a container data type requires recursing to resolve all the type sequence.
         Rule::container => {
+            let container_span = span_of(&data_type_pair);
             let mut container_inner = data_type_pair.into_inner();
 
-            // Extract the name of the container type (vec/option).
+            // Extract the name of the container type (vec/option/map/set/tuple).
             let container_type_name = container_inner
                 .next()
-                .ok_or(ParserError::DataTypeContainerName)
+                .ok_or(ParserError::DataTypeContainerName(container_span))
                 .inspect_err(|error| {
                     tracing::debug!(?error, "Failed to parse the container type name")
                 })?;
 
-            // Recursively parse the DataType inside the container.
-            let container_inner_data_type_pair = container_inner
-                .next()
-                .ok_or(ParserError::DataTypeContainerInnerType)
-                .inspect_err(|error| {
-                    tracing::debug!(?error, "Failed to parse the container inner type")
-                })?;
-            let inner_data_type = parse_data_type(container_inner_data_type_pair)?;
+            // Fixed-size arrays have their own rule, since they hold a size besides their type.
+            if container_type_name.as_rule() == Rule::array {
+                return parse_array(container_type_name);
+            }
 
-            // Match the container type name to build the DataType correctly.
             let container_type = match container_type_name.as_str() {
-                "vec" => {
-                    DataType::Container(misty_ast::ContainerType::Vec, Box::new(inner_data_type))
-                }
-                "option" => {
-                    DataType::Container(misty_ast::ContainerType::Option, Box::new(inner_data_type))
-                }
+                "vec" => ContainerType::Vec,
+                "option" => ContainerType::Option,
+                "map" => ContainerType::Map,
+                "set" => ContainerType::Set,
+                "tuple" => ContainerType::Tuple,
                 _ => unreachable!(),
             };
 
-            Ok(container_type)
+            // Recursively parse every DataType inside the container.
+            let inner_data_types = container_inner
+                .map(parse_data_type)
+                .collect::<Result<Vec<_>, _>>()?;
+            if inner_data_types.is_empty() {
+                tracing::debug!("Failed to parse the container inner type");
+                return Err(ParserError::DataTypeContainerInnerType(container_span));
+            }
+
+            // The grammar accepts any number of inner types, check the container can hold them.
+            if !container_type.arity().contains(&inner_data_types.len()) {
+                tracing::debug!(
+                    %container_type,
+                    count = inner_data_types.len(),
+                    "Wrong number of container inner types"
+                );
+                return Err(ParserError::DataTypeContainerArity(container_span));
+            }
+
+            Ok(DataType::Container(container_type, inner_data_types))
         }
         _ => unreachable!(),
     }
 }
+
+/// Parses a fixed-size array, made of its element DataType and its size.
+fn parse_array(pair: Pair<Rule>) -> Result<DataType, ParserError> {
+    let span = span_of(&pair);
+    let mut array_inner = pair.into_inner();
+
+    // Recursively parse the DataType of the elements.
+    let element_pair = array_inner
+        .next()
+        .ok_or(ParserError::DataTypeContainerInnerType(span))
+        .inspect_err(|error| tracing::debug!(?error, "Failed to parse the array element type"))?;
+    let element_data_type = parse_data_type(element_pair)?;
+
+    // Parse the size of the array, which the grammar guarantees to be made of digits only.
+    let size = array_inner
+        .next()
+        .and_then(|pair| pair.as_str().parse().ok())
+        .ok_or(ParserError::DataTypeArraySize(span))
+        .inspect_err(|error| tracing::debug!(?error, "Failed to parse the array size"))?;
+
+    Ok(DataType::Container(
+        ContainerType::Array(size),
+        vec![element_data_type],
+    ))
+}
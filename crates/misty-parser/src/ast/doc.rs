@@ -0,0 +1,23 @@
+use crate::pest_parser::Rule;
+use pest::iterators::Pairs;
+
+/// Parses the doc comments placed before a definition or a member.
+///
+/// Doc comments are consumed from the start of `pairs`, which is left at the first pair following
+/// them. Each line is returned without its `///` and the single space usually following it.
+pub fn parse_docs(pairs: &mut Pairs<Rule>) -> Vec<String> {
+    let mut docs = Vec::new();
+    while let Some(pair) = pairs
+        .peek()
+        .filter(|pair| pair.as_rule() == Rule::doc_comment)
+    {
+        pairs.next();
+        let line = pair
+            .as_str()
+            .trim_start_matches("///")
+            .trim_end_matches('\r');
+        docs.push(line.strip_prefix(' ').unwrap_or(line).to_string());
+    }
+
+    docs
+}
@@ -1,5 +1,5 @@
 use crate::ParserError;
-use crate::ast::parse_data_type;
+use crate::ast::{parse_annotations, parse_data_type, parse_docs, span_of};
 use crate::pest_parser::Rule;
 use misty_ast::{DataType, Function};
 use pest::iterators::Pair;
@@ -7,12 +7,15 @@ use pest::iterators::Pair;
 /// Parses a function declaration.
 #[tracing::instrument(skip(pair))]
 pub fn parse_function(pair: Pair<Rule>) -> Result<Function, ParserError> {
+    let span = span_of(&pair);
     let mut function_inner = pair.into_inner();
+    let docs = parse_docs(&mut function_inner);
+    let annotations = parse_annotations(&mut function_inner)?;
 
     // Extract the name of the function.
     let name = function_inner
         .next()
-        .ok_or(ParserError::FunctionName)
+        .ok_or(ParserError::FunctionName(span))
         .inspect_err(|error| tracing::debug!(?error, "Failed to parse the function name"))?
         .as_str()
         .to_string();
@@ -20,7 +23,7 @@ pub fn parse_function(pair: Pair<Rule>) -> Result<Function, ParserError> {
     // Parse the input argument of the function.
     let input_argument_pair = function_inner
         .next()
-        .ok_or(ParserError::FunctionArgument)
+        .ok_or(ParserError::FunctionArgument(span))
         .inspect_err(|error| {
             tracing::debug!(?error, "Failed to parse the function input argument")
         })?;
@@ -36,6 +39,9 @@ pub fn parse_function(pair: Pair<Rule>) -> Result<Function, ParserError> {
         name,
         input: input_argument,
         output: output_argument,
+        docs,
+        annotations,
+        span,
     })
 }
 
@@ -44,10 +50,11 @@ pub fn parse_function(pair: Pair<Rule>) -> Result<Function, ParserError> {
 /// It returns a boolean flag besides the [DataType]. This flag indicates if the argument is a
 /// stream or not.
 fn parse_function_argument(pair: Pair<Rule>) -> Result<(bool, DataType), ParserError> {
+    let span = span_of(&pair);
     let mut argument_inner = pair.into_inner();
     let mut argument_pair = argument_inner
         .next()
-        .ok_or(ParserError::FunctionArgument)
+        .ok_or(ParserError::FunctionArgument(span))
         .inspect_err(|error| {
             tracing::debug!(?error, "Failed to parse the function argument inner token")
         })?;
@@ -57,7 +64,7 @@ fn parse_function_argument(pair: Pair<Rule>) -> Result<(bool, DataType), ParserE
         // In case it is, we advance to the next token, that is the ACTUAL DataType.
         argument_pair = argument_inner
             .next()
-            .ok_or(ParserError::FunctionArgument)
+            .ok_or(ParserError::FunctionArgument(span))
             .inspect_err(|error| {
                 tracing::debug!(?error, "Failed to parse the DataType after the STREAM_KW")
             })?;
@@ -66,15 +73,13 @@ fn parse_function_argument(pair: Pair<Rule>) -> Result<(bool, DataType), ParserE
         false
     };
 
-    // Parse the DataType.
-    let data_type_pair = argument_pair
-        .into_inner()
-        .next()
-        .ok_or(ParserError::FunctionArgumentDataType)
-        .inspect_err(|error| {
-            tracing::debug!(?error, "Failed to parse the function argument DataType")
-        })?;
-    let data_type = parse_data_type(data_type_pair)?;
+    // Parse the DataType. The current token is the `field_type` itself, which is exactly what
+    // the DataType parser expects.
+    if argument_pair.as_rule() != Rule::field_type {
+        tracing::debug!(rule = ?argument_pair.as_rule(), "Function argument is not a DataType");
+        return Err(ParserError::FunctionArgumentDataType(span));
+    }
+    let data_type = parse_data_type(argument_pair)?;
 
     Ok((is_stream, data_type))
 }
@@ -1,13 +1,41 @@
+use crate::ParserError;
+use crate::ast::span_of;
 use crate::pest_parser::Rule;
+use misty_ast::{Import, ImportItem};
 use pest::iterators::Pair;
 
-/// The import statement is very simple to parse; we get each token in it and join every
-/// part with a dot.
+/// The import statement is very simple to parse; we get each segment of the module path and join
+/// every part with a dot.
 ///
-/// This creates an import string equal to how it's defined: `my_module.a.b.c`.
-pub fn parse_import(pair: Pair<Rule>) -> String {
-    pair.into_inner()
-        .map(|pair| pair.as_str())
-        .collect::<Vec<_>>()
-        .join(".")
+/// This creates an import path equal to how it's defined: `my_module.a.b.c`. The alias or the
+/// selected types follow the path.
+///
+/// Returns a [ParserError::Import] error if the statement holds anything else.
+pub fn parse_import(pair: Pair<Rule>) -> Result<Import, ParserError> {
+    let span = span_of(&pair);
+    let mut segments = Vec::new();
+    let mut alias = None;
+    let mut items = Vec::new();
+
+    for pair in pair.into_inner() {
+        match pair.as_rule() {
+            Rule::ident_snake => segments.push(pair.as_str()),
+            Rule::import_alias => alias = Some(pair.as_str().to_string()),
+            Rule::import_items => items.extend(pair.into_inner().map(|item| ImportItem {
+                name: item.as_str().to_string(),
+                span: span_of(&item),
+            })),
+            rule => {
+                tracing::debug!(?rule, "Unexpected rule in the Import");
+                return Err(ParserError::Import(span));
+            }
+        }
+    }
+
+    Ok(Import {
+        path: segments.join("."),
+        alias,
+        items,
+        span,
+    })
 }
@@ -1,5 +1,5 @@
 use crate::ParserError;
-use crate::ast::parse_function;
+use crate::ast::{parse_annotations, parse_docs, parse_function, span_of};
 use crate::pest_parser::Rule;
 use misty_ast::Interface;
 use pest::iterators::Pair;
@@ -7,12 +7,15 @@ use pest::iterators::Pair;
 /// Parses an Interface definition.
 #[tracing::instrument(skip(pair))]
 pub fn parse_interface(pair: Pair<Rule>) -> Result<Interface, ParserError> {
+    let span = span_of(&pair);
     let mut inner = pair.into_inner();
+    let docs = parse_docs(&mut inner);
+    let annotations = parse_annotations(&mut inner)?;
 
     // Extract the name of the interface.
     let name = inner
         .next()
-        .ok_or(ParserError::InterfaceName)
+        .ok_or(ParserError::InterfaceName(span))
         .inspect_err(|error| tracing::debug!(?error, "Failed to parse the interface name"))?
         .as_str()
         .to_string();
@@ -24,5 +27,11 @@ pub fn parse_interface(pair: Pair<Rule>) -> Result<Interface, ParserError> {
         functions.push(function);
     }
 
-    Ok(Interface { name, functions })
+    Ok(Interface {
+        name,
+        functions,
+        docs,
+        annotations,
+        span,
+    })
 }
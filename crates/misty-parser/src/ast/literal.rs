@@ -0,0 +1,52 @@
+use crate::ParserError;
+use crate::ast::span_of;
+use crate::pest_parser::Rule;
+use misty_ast::Literal;
+use pest::iterators::Pair;
+
+/// Parses a Literal, like the default value of a field.
+///
+/// Returns a [ParserError::Literal] error if a number does not fit in its representation.
+#[tracing::instrument(skip(pair))]
+pub fn parse_literal(pair: Pair<Rule>) -> Result<Literal, ParserError> {
+    let span = span_of(&pair);
+
+    // Extract the actual kind of literal, the `literal` rule only wraps it.
+    let literal_pair = pair
+        .into_inner()
+        .next()
+        .ok_or(ParserError::Literal(span))
+        .inspect_err(|error| tracing::debug!(?error, "Failed to parse the Literal"))?;
+    let text = literal_pair.as_str();
+
+    match literal_pair.as_rule() {
+        Rule::literal_int => text
+            .parse()
+            .map(Literal::Integer)
+            .map_err(|_| ParserError::Literal(span))
+            .inspect_err(|error| tracing::debug!(?error, "Failed to parse the integer")),
+        Rule::literal_float => text
+            .parse()
+            .map(Literal::Float)
+            .map_err(|_| ParserError::Literal(span))
+            .inspect_err(|error| tracing::debug!(?error, "Failed to parse the float")),
+        Rule::literal_bool => Ok(Literal::Bool(text == "true")),
+        Rule::literal_str => Ok(Literal::Str(unescape(&text[1..text.len() - 1]))),
+        Rule::literal_none => Ok(Literal::None),
+        _ => unreachable!(),
+    }
+}
+
+/// Resolves the escape sequences of a string literal, `\"` and `\\`, which the grammar guarantees
+/// to be the only ones.
+fn unescape(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(char) = chars.next() {
+        match char {
+            '\\' => unescaped.extend(chars.next()),
+            _ => unescaped.push(char),
+        }
+    }
+    unescaped
+}
@@ -1,6 +1,7 @@
 use crate::ParserError;
+use crate::ast::{parse_annotations, parse_docs, span_of};
 use crate::pest_parser::Rule;
-use misty_ast::Enum;
+use misty_ast::{Enum, EnumVariant};
 use pest::iterators::Pair;
 
 /// Parses an Enum definition.
@@ -9,18 +10,58 @@ use pest::iterators::Pair;
 /// Enum type.
 #[tracing::instrument(skip(pair))]
 pub fn parse_enum(pair: Pair<Rule>) -> Result<Enum, ParserError> {
+    let span = span_of(&pair);
     let mut inner = pair.into_inner();
+    let docs = parse_docs(&mut inner);
+    let annotations = parse_annotations(&mut inner)?;
 
     // Extract the name of this Enum.
     let name = inner
         .next()
-        .ok_or(ParserError::EnumName)
+        .ok_or(ParserError::EnumName(span))
         .inspect_err(|error| tracing::debug!(?error, "Failed to parse the Enum type name"))?
         .as_str()
         .to_string();
 
     // Extract all the variants of this Enum.
-    let variants = inner.map(|pair| pair.as_str().to_string()).collect();
+    let mut variants = Vec::new();
+    for variant_pair in inner {
+        let variant_span = span_of(&variant_pair);
+        let mut variant_inner = variant_pair.into_inner();
+        let docs = parse_docs(&mut variant_inner);
+        let annotations = parse_annotations(&mut variant_inner)?;
 
-    Ok(Enum { name, variants })
+        // Extract the Variant name.
+        let name = variant_inner
+            .next()
+            .ok_or(ParserError::EnumVariantName(variant_span))
+            .inspect_err(|error| tracing::debug!(?error, "Failed to parse the Variant name"))?
+            .as_str()
+            .to_string();
+
+        // Parse the explicit value of this Variant, if any. The grammar guarantees it's made of
+        // digits only, so it can only fail if it does not fit in a u64.
+        let value = variant_inner
+            .next()
+            .map(|value_pair| value_pair.as_str().parse())
+            .transpose()
+            .map_err(|_| ParserError::EnumVariantValue(variant_span))
+            .inspect_err(|error| tracing::debug!(?error, "Failed to parse the Variant value"))?;
+
+        variants.push(EnumVariant {
+            name,
+            value,
+            docs,
+            annotations,
+            span: variant_span,
+        });
+    }
+
+    Ok(Enum {
+        name,
+        variants,
+        docs,
+        annotations,
+        span,
+    })
 }
@@ -0,0 +1,62 @@
+use crate::ParserError;
+use crate::ast::{parse_annotations, parse_data_type, parse_docs, span_of};
+use crate::pest_parser::Rule;
+use misty_ast::{Union, UnionVariant};
+use pest::iterators::Pair;
+
+/// Parses a Union definition.
+#[tracing::instrument(skip(pair))]
+pub fn parse_union(pair: Pair<Rule>) -> Result<Union, ParserError> {
+    let span = span_of(&pair);
+    let mut inner = pair.into_inner();
+    let docs = parse_docs(&mut inner);
+    let annotations = parse_annotations(&mut inner)?;
+
+    // Extract the name of this Union.
+    let name = inner
+        .next()
+        .ok_or(ParserError::UnionName(span))
+        .inspect_err(|error| tracing::debug!(?error, "Failed to parse the Union type name"))?
+        .as_str()
+        .to_string();
+
+    // Parse the variants declared by this Union.
+    let mut variants = Vec::new();
+    for variant_pair in inner {
+        let variant_span = span_of(&variant_pair);
+        let mut variant_inner = variant_pair.into_inner();
+        let docs = parse_docs(&mut variant_inner);
+        let annotations = parse_annotations(&mut variant_inner)?;
+
+        // Extract the Variant name.
+        let name = variant_inner
+            .next()
+            .ok_or(ParserError::UnionVariantName(variant_span))
+            .inspect_err(|error| tracing::debug!(?error, "Failed to parse the Variant name"))?
+            .as_str()
+            .to_string();
+
+        // Parse the DataType held by this Variant.
+        let variant_type_pair = variant_inner
+            .next()
+            .ok_or(ParserError::UnionVariantDataType(variant_span))
+            .inspect_err(|error| tracing::debug!(?error, "Failed to parse the Variant type"))?;
+        let variant_type = parse_data_type(variant_type_pair)?;
+
+        variants.push(UnionVariant {
+            name,
+            variant_type,
+            docs,
+            annotations,
+            span: variant_span,
+        });
+    }
+
+    Ok(Union {
+        name,
+        variants,
+        docs,
+        annotations,
+        span,
+    })
+}
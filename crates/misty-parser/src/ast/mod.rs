@@ -2,24 +2,33 @@
 //!
 //! The main entrypoint of this module is the [parse] function, but other functions are available
 //! to parse individual AST nodes.
+mod annotation;
 mod data_type;
+mod doc;
 mod function;
 mod import;
 mod interface;
+mod literal;
 mod misty_enum;
+mod misty_union;
 mod schema;
 
+pub(crate) use annotation::parse_annotations;
 pub(crate) use data_type::parse_data_type;
+pub(crate) use doc::parse_docs;
 pub(crate) use function::parse_function;
 pub(crate) use import::parse_import;
 pub(crate) use interface::parse_interface;
+pub(crate) use literal::parse_literal;
 pub(crate) use misty_enum::parse_enum;
+pub(crate) use misty_union::parse_union;
 pub(crate) use schema::parse_schema;
 
 use crate::ParserError;
 use crate::pest_parser::{MistyPestParser, Rule};
-use misty_ast::{Definition, File};
+use misty_ast::{Definition, File, Span};
 use pest::Parser;
+use pest::iterators::Pair;
 
 #[tracing::instrument(skip(source))]
 pub fn parse(source: &str) -> Result<File, ParserError> {
@@ -31,16 +40,19 @@ pub fn parse(source: &str) -> Result<File, ParserError> {
         .ok_or(ParserError::RootNode)
         .inspect_err(|error| tracing::debug!(?error, "No root node was found in the source"))?;
 
+    let span = span_of(&root);
+
     // Iterate over every remaining Pairs inside the root node and extract the imports list and
     // definitions list.
     let mut imports = Vec::new();
     let mut definitions = Vec::new();
     for pair in root.into_inner() {
         match pair.as_rule() {
-            Rule::import_stmt => imports.push(parse_import(pair)),
+            Rule::import_stmt => imports.push(parse_import(pair)?),
             Rule::interface_def => definitions.push(Definition::Interface(parse_interface(pair)?)),
             Rule::schema_def => definitions.push(Definition::Schema(parse_schema(pair)?)),
             Rule::enum_def => definitions.push(Definition::Enum(parse_enum(pair)?)),
+            Rule::union_def => definitions.push(Definition::Union(parse_union(pair)?)),
             Rule::EOI => (),
             _ => unreachable!(),
         }
@@ -50,5 +62,122 @@ pub fn parse(source: &str) -> Result<File, ParserError> {
     Ok(File {
         imports,
         definitions,
+        span,
     })
 }
+
+/// Captures the [Span] of a pest [Pair] so it can be stored in the AST.
+pub(crate) fn span_of(pair: &Pair<Rule>) -> Span {
+    let pest_span = pair.as_span();
+    let (line, column) = pest_span.start_pos().line_col();
+
+    Span {
+        start: pest_span.start(),
+        end: pest_span.end(),
+        line,
+        column,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parse;
+    use misty_ast::Definition;
+
+    #[test]
+    fn it_captures_spans() {
+        let source = "import users;\n\nschema Person {\n    owner: users.User;\n}\n";
+        let file = parse(source).unwrap();
+
+        assert_eq!(file.imports[0].path, "users");
+        assert_eq!(&source[file.imports[0].span.range()], "import users;");
+
+        let Definition::Schema(schema) = &file.definitions[0] else {
+            panic!("expected a schema");
+        };
+        assert_eq!((schema.span.line, schema.span.column), (3, 1));
+        assert_eq!(
+            (schema.fields[0].span.line, schema.fields[0].span.column),
+            (4, 5)
+        );
+        assert_eq!(&source[schema.fields[0].span.range()], "owner: users.User;");
+    }
+
+    #[test]
+    fn it_parses_import_forms() {
+        let source = "import ml.analyzer;
+import ml.analyzer as an;
+                      import ml.analyzer.{Analysis, Report,};";
+        let file = parse(source).unwrap();
+
+        let forms = file
+            .imports
+            .iter()
+            .map(|import| {
+                let items = import.items.iter().map(|item| item.name.as_str());
+                (import.path.as_str(), import.qualifier(), items.collect())
+            })
+            .collect::<Vec<(_, _, Vec<_>)>>();
+        assert_eq!(
+            forms,
+            vec![
+                ("ml.analyzer", Some("ml.analyzer"), vec![]),
+                ("ml.analyzer", Some("an"), vec![]),
+                ("ml.analyzer", None, vec!["Analysis", "Report"]),
+            ]
+        );
+        assert_eq!(&source[file.imports[2].items[1].span.range()], "Report");
+
+        assert_eq!(
+            file.qualify("jobs", "an.Analysis"),
+            ("ml.analyzer", "Analysis")
+        );
+        assert_eq!(file.qualify("jobs", "Report"), ("ml.analyzer", "Report"));
+        assert_eq!(file.qualify("jobs", "Job"), ("jobs", "Job"));
+        assert!(parse("import ml.analyzer.{};").is_err());
+    }
+
+    #[test]
+    fn it_keeps_doc_comments() {
+        let source = "/// A person.\r\n///\n///   Indented.\n// Not a doc.\n\
+                      //// Not a doc either.\n\
+                      @deprecated schema Person {\n    /// The name.\n    name: str;\n}\n\
+                      enum Role { /// Can do anything.\n ADMIN, GUEST }";
+        let file = parse(source).unwrap();
+
+        let Definition::Schema(schema) = &file.definitions[0] else {
+            panic!("expected a schema");
+        };
+        assert_eq!(schema.docs, vec!["A person.", "", "  Indented."]);
+        assert_eq!(schema.fields[0].docs, vec!["The name."]);
+
+        let Definition::Enum(misty_enum) = &file.definitions[1] else {
+            panic!("expected an enum");
+        };
+        assert!(misty_enum.docs.is_empty());
+        assert_eq!(misty_enum.variants[0].docs, vec!["Can do anything."]);
+        assert!(misty_enum.variants[1].docs.is_empty());
+    }
+
+    #[test]
+    fn it_ignores_doc_comments_before_imports() {
+        let file = parse("/// Users.\nimport users;\nschema S {}").unwrap();
+        assert_eq!(file.imports.len(), 1);
+        assert!(file.definitions[0].docs().is_empty());
+    }
+
+    #[test]
+    fn it_ignores_trailing_doc_comments() {
+        let file = parse("schema S {\n    id: u64;\n    /// Trailing.\n}").unwrap();
+        let Definition::Schema(schema) = &file.definitions[0] else {
+            panic!("expected a schema");
+        };
+        assert!(schema.fields[0].docs.is_empty());
+    }
+
+    #[test]
+    fn it_ignores_doc_comments_at_the_end_of_files() {
+        let file = parse("/// A schema.\nschema S {}\n/// The end.").unwrap();
+        assert_eq!(file.definitions[0].docs(), ["A schema."]);
+    }
+}
@@ -1,5 +1,5 @@
 use crate::ParserError;
-use crate::ast::parse_data_type;
+use crate::ast::{parse_annotations, parse_data_type, parse_docs, parse_literal, span_of};
 use crate::pest_parser::Rule;
 use misty_ast::{Field, Schema};
 use pest::iterators::Pair;
@@ -7,12 +7,15 @@ use pest::iterators::Pair;
 /// Parses a Schema definition.
 #[tracing::instrument(skip(pair))]
 pub fn parse_schema(pair: Pair<Rule>) -> Result<Schema, ParserError> {
+    let span = span_of(&pair);
     let mut inner = pair.into_inner();
+    let docs = parse_docs(&mut inner);
+    let annotations = parse_annotations(&mut inner)?;
 
     // Extract the Schema type name.
     let name = inner
         .next()
-        .ok_or(ParserError::SchemaName)
+        .ok_or(ParserError::SchemaName(span))
         .inspect_err(|error| tracing::debug!(?error, "Failed to parse the Schema type name"))?
         .as_str()
         .to_string();
@@ -20,12 +23,15 @@ pub fn parse_schema(pair: Pair<Rule>) -> Result<Schema, ParserError> {
     // Parse the fields declared by this Schema.
     let mut fields = Vec::new();
     for field_pair in inner {
+        let field_span = span_of(&field_pair);
         let mut field_inner = field_pair.into_inner();
+        let docs = parse_docs(&mut field_inner);
+        let annotations = parse_annotations(&mut field_inner)?;
 
         // Extract the Field name.
         let name = field_inner
             .next()
-            .ok_or(ParserError::FieldName)
+            .ok_or(ParserError::FieldName(field_span))
             .inspect_err(|error| tracing::debug!(?error, "Failed to parse the Field type name"))?
             .as_str()
             .to_string();
@@ -33,13 +39,29 @@ pub fn parse_schema(pair: Pair<Rule>) -> Result<Schema, ParserError> {
         // Parse the DataType of this Field.
         let field_type_pair = field_inner
             .next()
-            .ok_or(ParserError::FieldDataType)
+            .ok_or(ParserError::FieldDataType(field_span))
             .inspect_err(|error| tracing::debug!(?error, "Failed to parse the Field type"))?;
         let field_type = parse_data_type(field_type_pair)?;
 
+        // Parse the default value of this Field, if any.
+        let default = field_inner.next().map(parse_literal).transpose()?;
+
         // Add this Field to the list of fields.
-        fields.push(Field { name, field_type });
+        fields.push(Field {
+            name,
+            field_type,
+            default,
+            docs,
+            annotations,
+            span: field_span,
+        });
     }
 
-    Ok(Schema { name, fields })
+    Ok(Schema {
+        name,
+        fields,
+        docs,
+        annotations,
+        span,
+    })
 }
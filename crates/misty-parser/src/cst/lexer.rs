@@ -0,0 +1,117 @@
+use crate::cst::{SyntaxElement, SyntaxToken, TokenKind};
+use misty_ast::Span;
+
+/// Splits the text found between grammar pairs into tokens.
+///
+/// That text is made of whitespace, comments, and the keywords and punctuation consumed by the
+/// grammar without producing pairs.
+pub struct Lexer<'s> {
+    source: &'s str,
+
+    /// Byte offset where every line of the source starts.
+    line_starts: Vec<usize>,
+}
+
+impl<'s> Lexer<'s> {
+    pub fn new(source: &'s str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(index, _)| index + 1))
+            .collect();
+
+        Self {
+            source,
+            line_starts,
+        }
+    }
+
+    /// Builds the [Span] of a byte range of the source.
+    ///
+    /// Columns count characters, the same way pest does.
+    pub fn span(&self, start: usize, end: usize) -> Span {
+        let line = self
+            .line_starts
+            .partition_point(|&line_start| line_start <= start);
+        let line_start = self.line_starts[line - 1];
+
+        Span {
+            start,
+            end,
+            line,
+            column: self.source[line_start..start].chars().count() + 1,
+        }
+    }
+
+    /// Builds a token covering a byte range of the source.
+    pub fn token(&self, kind: TokenKind, start: usize, end: usize) -> SyntaxToken {
+        SyntaxToken {
+            kind,
+            text: self.source[start..end].to_string(),
+            span: self.span(start, end),
+        }
+    }
+
+    /// Splits a byte range of the source into tokens, appended to `elements`.
+    pub fn lex(&self, start: usize, end: usize, elements: &mut Vec<SyntaxElement>) {
+        let mut offset = start;
+        while offset < end {
+            let rest = &self.source[offset..end];
+            let (kind, length) = if rest.starts_with(is_whitespace) {
+                let length = rest.find(|c| !is_whitespace(c)).unwrap_or(rest.len());
+                (TokenKind::Whitespace, length)
+            } else if rest.starts_with("/*") {
+                (TokenKind::BlockComment, block_comment_length(rest))
+            } else if rest.starts_with("//") {
+                let length = rest.find('\n').unwrap_or(rest.len());
+                (TokenKind::LineComment, length)
+            } else if rest.starts_with(is_word) {
+                let length = rest.find(|c| !is_word(c)).unwrap_or(rest.len());
+                (TokenKind::Keyword, length)
+            } else {
+                let length = rest.chars().next().map_or(1, char::len_utf8);
+                (TokenKind::Punctuation, length)
+            };
+
+            elements.push(SyntaxElement::Token(self.token(
+                kind,
+                offset,
+                offset + length,
+            )));
+            offset += length;
+        }
+    }
+}
+
+/// Whitespace as defined by the grammar.
+fn is_whitespace(c: char) -> bool {
+    matches!(c, ' ' | '\t' | '\r' | '\n')
+}
+
+/// Characters making up keywords.
+fn is_word(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// Length of the block comment starting `text`, including its nested comments.
+///
+/// Unterminated comments are rejected by the grammar, but they would span the whole text.
+fn block_comment_length(text: &str) -> usize {
+    let mut depth = 0;
+    let mut offset = 0;
+    while offset < text.len() {
+        let rest = &text[offset..];
+        if rest.starts_with("/*") {
+            depth += 1;
+            offset += 2;
+        } else if rest.starts_with("*/") {
+            depth -= 1;
+            offset += 2;
+            if depth == 0 {
+                break;
+            }
+        } else {
+            offset += rest.chars().next().map_or(1, char::len_utf8);
+        }
+    }
+
+    offset
+}
@@ -0,0 +1,353 @@
+//! The cst module contains a lossless concrete syntax tree of Misty files.
+//!
+//! Unlike the AST returned by [parse](crate::parse), the concrete syntax tree keeps every single
+//! character of the source: keywords, punctuation, whitespace and comments are stored as tokens,
+//! in order, inside the nodes of the grammar constructs they belong to. Printing a tree gives back
+//! the exact source it was parsed from, which makes it the right base for formatters and
+//! refactoring tools.
+//!
+//! Trivia belong to the innermost node enclosing them. Trivia between two tokens of a construct are
+//! tokens of its node, so comments placed before a definition are tokens of the file node, while
+//! comments inside a schema body are tokens of the schema node. Trivia following the last token of
+//! a construct are never part of its node: a comment after an enum variant, with or without a
+//! value, is a token of the enum node.
+mod lexer;
+
+use crate::ParserError;
+use crate::pest_parser::{MistyPestParser, Rule};
+use lexer::Lexer;
+use misty_ast::Span;
+use pest::Parser;
+use pest::iterators::Pair;
+use std::fmt;
+
+/// Kinds of nodes in the concrete syntax tree, one per grammar construct.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NodeKind {
+    File,
+    Import,
+    ImportItems,
+    Annotation,
+    AnnotationArgument,
+    Literal,
+    Enum,
+    EnumVariant,
+    Schema,
+    Field,
+    Union,
+    UnionVariant,
+    Interface,
+    Function,
+    FunctionArgument,
+    FunctionReturn,
+    DataType,
+    Container,
+    Array,
+}
+
+/// Kinds of tokens in the concrete syntax tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TokenKind {
+    /// Spaces, tabs and line breaks.
+    Whitespace,
+
+    /// A `// ...` comment, up to the end of its line.
+    LineComment,
+
+    /// A `/* ... */` comment, possibly nested.
+    BlockComment,
+
+    /// A `/// ...` doc comment, up to the end of its line.
+    DocComment,
+
+    /// Reserved words, like `schema`, `stream`, `vec` or `u32`.
+    Keyword,
+
+    /// Names of definitions, members, modules and types.
+    Identifier,
+
+    /// Numbers, booleans, strings and `none`, including enum values and array sizes.
+    Literal,
+
+    /// A single punctuation character, like `{`, `;` or `@`.
+    Punctuation,
+}
+
+/// A node of the concrete syntax tree.
+#[derive(Debug, Clone)]
+pub struct SyntaxNode {
+    /// The grammar construct of this node.
+    pub kind: NodeKind,
+
+    /// Location of the node, from its first to its last token.
+    pub span: Span,
+
+    /// Nodes and tokens of this node, in source order.
+    pub children: Vec<SyntaxElement>,
+}
+
+/// A child of a [SyntaxNode].
+#[derive(Debug, Clone)]
+pub enum SyntaxElement {
+    Node(SyntaxNode),
+    Token(SyntaxToken),
+}
+
+/// A token of the concrete syntax tree.
+#[derive(Debug, Clone)]
+pub struct SyntaxToken {
+    /// The kind of this token.
+    pub kind: TokenKind,
+
+    /// The text of this token, exactly as written in the source.
+    pub text: String,
+
+    /// Location of the token.
+    pub span: Span,
+}
+
+impl SyntaxNode {
+    /// Every token of this node and its descendants, in source order.
+    pub fn tokens(&self) -> Box<dyn Iterator<Item = &SyntaxToken> + '_> {
+        Box::new(self.children.iter().flat_map(|child| match child {
+            SyntaxElement::Node(node) => node.tokens(),
+            SyntaxElement::Token(token) => Box::new(std::iter::once(token)),
+        }))
+    }
+
+    /// Direct child nodes of this node, in source order.
+    pub fn nodes(&self) -> impl Iterator<Item = &SyntaxNode> {
+        self.children.iter().filter_map(|child| match child {
+            SyntaxElement::Node(node) => Some(node),
+            SyntaxElement::Token(_) => None,
+        })
+    }
+}
+
+impl SyntaxToken {
+    /// Checks if this token is whitespace or a regular comment, which carry no meaning.
+    ///
+    /// Doc comments are not trivia, they are kept in the AST.
+    pub fn is_trivia(&self) -> bool {
+        matches!(
+            self.kind,
+            TokenKind::Whitespace | TokenKind::LineComment | TokenKind::BlockComment
+        )
+    }
+}
+
+/// Prints the exact source the node was parsed from.
+impl fmt::Display for SyntaxNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.tokens().try_for_each(|token| f.write_str(&token.text))
+    }
+}
+
+/// What a grammar rule becomes in the concrete syntax tree.
+enum Element {
+    Node(NodeKind),
+    Token(TokenKind),
+
+    /// Rules never producing pairs, or producing empty ones like the end of the file.
+    Hidden,
+}
+
+/// Maps a grammar rule to its place in the concrete syntax tree.
+fn classify(rule: Rule) -> Element {
+    match rule {
+        Rule::file => Element::Node(NodeKind::File),
+        Rule::import_stmt => Element::Node(NodeKind::Import),
+        Rule::import_items => Element::Node(NodeKind::ImportItems),
+        Rule::annotation => Element::Node(NodeKind::Annotation),
+        Rule::annotation_arg => Element::Node(NodeKind::AnnotationArgument),
+        Rule::literal => Element::Node(NodeKind::Literal),
+        Rule::enum_def => Element::Node(NodeKind::Enum),
+        Rule::enum_variant => Element::Node(NodeKind::EnumVariant),
+        Rule::schema_def => Element::Node(NodeKind::Schema),
+        Rule::field_def => Element::Node(NodeKind::Field),
+        Rule::union_def => Element::Node(NodeKind::Union),
+        Rule::union_variant => Element::Node(NodeKind::UnionVariant),
+        Rule::interface_def => Element::Node(NodeKind::Interface),
+        Rule::fn_def => Element::Node(NodeKind::Function),
+        Rule::fn_arg => Element::Node(NodeKind::FunctionArgument),
+        Rule::fn_return => Element::Node(NodeKind::FunctionReturn),
+        Rule::field_type => Element::Node(NodeKind::DataType),
+        Rule::container => Element::Node(NodeKind::Container),
+        Rule::array => Element::Node(NodeKind::Array),
+        Rule::doc_comment => Element::Token(TokenKind::DocComment),
+        Rule::STREAM_KW | Rule::primitive | Rule::container_type => {
+            Element::Token(TokenKind::Keyword)
+        }
+        Rule::ident_pascal
+        | Rule::ident_snake
+        | Rule::ident_scream
+        | Rule::user_type
+        | Rule::import_alias => Element::Token(TokenKind::Identifier),
+        Rule::literal_int
+        | Rule::literal_float
+        | Rule::literal_bool
+        | Rule::literal_str
+        | Rule::literal_none
+        | Rule::enum_value
+        | Rule::array_size => Element::Token(TokenKind::Literal),
+        Rule::EOI
+        | Rule::WHITESPACE
+        | Rule::COMMENT
+        | Rule::block_comment
+        | Rule::doc_target
+        | Rule::IMPORT_KW
+        | Rule::INTERFACE_KW
+        | Rule::SCHEMA_KW
+        | Rule::ENUM_KW
+        | Rule::UNION_KW
+        | Rule::FN_KW
+        | Rule::AS_KW => Element::Hidden,
+    }
+}
+
+/// Parses a Misty source into its lossless concrete syntax tree.
+///
+/// The source must be valid Misty syntax, the same errors as [parse](crate::parse) are reported.
+#[tracing::instrument(skip(source))]
+pub fn parse_cst(source: &str) -> Result<SyntaxNode, ParserError> {
+    // Try to parse the root rule which is our `file` grammar node.
+    let mut pairs = MistyPestParser::parse(Rule::file, source)
+        .inspect_err(|error| tracing::debug!(?error, "Failed to parse the root rule"))?;
+    let root = pairs
+        .next()
+        .ok_or(ParserError::RootNode)
+        .inspect_err(|error| tracing::debug!(?error, "No root node was found in the source"))?;
+
+    // The root pair spans the whole source, from the start to the end of the file.
+    let lexer = Lexer::new(source);
+    let file = build_node(&lexer, NodeKind::File, root);
+
+    Ok(file)
+}
+
+/// Helper function to build the node of a pair, lexing the text between its inner pairs.
+fn build_node(lexer: &Lexer, kind: NodeKind, pair: Pair<Rule>) -> SyntaxNode {
+    let pest_span = pair.as_span();
+    let mut children = Vec::new();
+    let mut cursor = pest_span.start();
+
+    for inner in pair.into_inner() {
+        let element = classify(inner.as_rule());
+        if matches!(element, Element::Hidden) {
+            continue;
+        }
+
+        let (start, end) = (inner.as_span().start(), inner.as_span().end());
+        lexer.lex(cursor, start, &mut children);
+        match element {
+            Element::Node(kind) => {
+                let mut node = build_node(lexer, kind, inner);
+                let trailing = split_trailing_trivia(&mut node);
+                children.push(SyntaxElement::Node(node));
+                children.extend(trailing);
+            }
+            Element::Token(kind) => {
+                children.push(SyntaxElement::Token(lexer.token(kind, start, end)))
+            }
+            Element::Hidden => (),
+        }
+        cursor = end;
+    }
+    lexer.lex(cursor, pest_span.end(), &mut children);
+
+    SyntaxNode {
+        kind,
+        span: lexer.span(pest_span.start(), pest_span.end()),
+        children,
+    }
+}
+
+/// Helper function to take the trivia following the last token of a node out of it.
+///
+/// Pest skips trivia while looking for an absent optional part at the end of a rule, like the value
+/// of an enum variant, which makes them part of the pair of the rule. They are given back to the
+/// enclosing node so every node ends at its last token.
+fn split_trailing_trivia(node: &mut SyntaxNode) -> Vec<SyntaxElement> {
+    let count = node
+        .children
+        .iter()
+        .rev()
+        .take_while(|child| matches!(child, SyntaxElement::Token(token) if token.is_trivia()))
+        .count();
+    let trailing = node.children.split_off(node.children.len() - count);
+    if let Some(SyntaxElement::Token(token)) = trailing.first() {
+        node.span.end = token.span.start;
+    }
+
+    trailing
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cst::{NodeKind, SyntaxElement, SyntaxNode, TokenKind, parse_cst};
+
+    #[test]
+    fn it_is_lossless() {
+        let source = "  // Leading comment.\r\nimport users;\n\n/* A /* nested */ comment. */\n\
+                      /// A person.\n@deprecated(\"use v2\")\nschema Person {\n    \
+                      owner : users.User; // Trailing comment.\n    \
+                      tags: map<str,  vec<u8>> = none;\n}\n\
+                      enum Role { ADMIN = 1, /* inline */ GUEST, }\n\
+                      interface Jobs { fn list(stream u32): option<str>; }\n// The end.";
+        assert!(crate::parse(source).is_ok());
+        let cst = parse_cst(source).unwrap();
+        assert_eq!(cst.to_string(), source);
+        assert_eq!((cst.span.start, cst.span.end), (0, source.len()));
+
+        let comments = cst
+            .tokens()
+            .filter(|token| token.is_trivia() && token.kind != TokenKind::Whitespace)
+            .map(|token| (token.kind, token.text.as_str(), token.span.line))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            comments,
+            vec![
+                (TokenKind::LineComment, "// Leading comment.\r", 1),
+                (TokenKind::BlockComment, "/* A /* nested */ comment. */", 4),
+                (TokenKind::LineComment, "// Trailing comment.", 8),
+                (TokenKind::BlockComment, "/* inline */", 11),
+                (TokenKind::LineComment, "// The end.", 13),
+            ]
+        );
+
+        // Doc comments and annotations belong to the schema they document.
+        let schema = cst.nodes().nth(1).unwrap();
+        assert_eq!(schema.kind, NodeKind::Schema);
+        assert!(matches!(
+            &schema.children[0],
+            SyntaxElement::Token(token) if token.kind == TokenKind::DocComment
+        ));
+        assert!(schema.to_string().ends_with("= none;\n}"));
+    }
+
+    #[test]
+    fn it_attaches_trailing_trivia() {
+        let cst = parse_cst("enum E { A = 1 /* a */, B /* b */ }").unwrap();
+        let misty_enum = cst.nodes().next().unwrap();
+        let comments_of = |node: &SyntaxNode| {
+            node.children
+                .iter()
+                .filter_map(|child| match child {
+                    SyntaxElement::Token(token) if token.kind == TokenKind::BlockComment => {
+                        Some(token.text.clone())
+                    }
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+
+        // Comments following a variant are the enum's, whether the variant has a value or not.
+        assert_eq!(comments_of(misty_enum), vec!["/* a */", "/* b */"]);
+        let variants = misty_enum.nodes().collect::<Vec<_>>();
+        assert!(comments_of(variants[0]).is_empty());
+        assert!(comments_of(variants[1]).is_empty());
+        assert_eq!(variants[0].to_string(), "A = 1");
+        assert_eq!(variants[1].to_string(), "B");
+        assert_eq!((variants[1].span.start, variants[1].span.end), (24, 25));
+    }
+}
@@ -0,0 +1,479 @@
+//! Compiler-style rendering of parser and validation errors.
+//!
+//! A [Diagnostic] is built from a [ParserError] or a [ValidationError], warnings included, and
+//! rendered against the source of the module it was found in:
+//!
+//! ```text
+//! error: type `Tag` was not found
+//!  --> users.misty:2:5
+//!   |
+//! 2 |     tags: vec<Tag>;
+//!   |     ^^^^^^^^^^^^^^^
+//!   |
+//!   = help: declare `Tag` in this module, or import the module declaring it
+//! ```
+use crate::ParserError;
+use crate::pest_parser::Rule;
+use crate::validator::{Severity, ValidationError};
+use misty_ast::Span;
+use pest::error::{Error as PestError, InputLocation, LineColLocation};
+use std::fmt::Write;
+
+/// A report of a single error or warning, ready to be rendered.
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    /// Whether this reports an error or a warning.
+    pub severity: Severity,
+
+    /// Short description of the error, using Misty terminology.
+    pub message: String,
+
+    /// Location of the error in its source, when known.
+    pub span: Option<Span>,
+
+    /// Hint on how to fix the error.
+    pub help: Option<String>,
+}
+
+impl Diagnostic {
+    /// Renders the diagnostic against the `source` it was found in.
+    ///
+    /// The `file_name` is only displayed, it's never read.
+    pub fn render(&self, file_name: &str, source: &str) -> String {
+        let level = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        let mut report = format!("{level}: {}\n", self.message);
+
+        match self.span {
+            Some(span) => {
+                let line = source
+                    .lines()
+                    .nth(span.line.saturating_sub(1))
+                    .unwrap_or("");
+                let gutter = " ".repeat(span.line.to_string().len());
+
+                // Underline the span, up to the end of its first line. Empty spans, like the end
+                // of the file, still get a single caret.
+                let line_start = source
+                    .get(..span.start)
+                    .and_then(|text| text.rfind('\n'))
+                    .map(|index| index + 1)
+                    .unwrap_or(0);
+                let underline_end = span.end.min(line_start + line.len());
+                let padding = source
+                    .get(line_start..span.start)
+                    .map(|text| text.chars().count())
+                    .unwrap_or(0);
+                let width = source
+                    .get(span.start..underline_end)
+                    .map(|text| text.chars().count())
+                    .unwrap_or(0)
+                    .max(1);
+
+                let _ = writeln!(report, "{gutter}--> {file_name}:{span}");
+                let _ = writeln!(report, "{gutter} |");
+                let _ = writeln!(report, "{} | {line}", span.line);
+                let _ = writeln!(
+                    report,
+                    "{gutter} | {}{}",
+                    " ".repeat(padding),
+                    "^".repeat(width)
+                );
+
+                if let Some(help) = &self.help {
+                    let _ = writeln!(report, "{gutter} |");
+                    let _ = writeln!(report, "{gutter} = help: {help}");
+                }
+            }
+            None => {
+                let _ = writeln!(report, " --> {file_name}");
+                if let Some(help) = &self.help {
+                    let _ = writeln!(report, "  = help: {help}");
+                }
+            }
+        }
+
+        report
+    }
+}
+
+impl From<&ParserError> for Diagnostic {
+    fn from(error: &ParserError) -> Self {
+        let (message, span) = match error {
+            ParserError::Pest(error) => return pest_diagnostic(error),
+            ParserError::Validation(error) => return Diagnostic::from(error.as_ref()),
+            ParserError::RootNode => ("no definitions were found in the source", None),
+            ParserError::EnumName(span) => ("invalid or missing enum name", Some(*span)),
+            ParserError::SchemaName(span) => ("invalid or missing schema name", Some(*span)),
+            ParserError::FieldName(span) => ("invalid or missing field name", Some(*span)),
+            ParserError::Import(span) => ("invalid import", Some(*span)),
+            ParserError::AnnotationName(span) => {
+                ("invalid or missing annotation name", Some(*span))
+            }
+            ParserError::EnumVariantName(span) => {
+                ("invalid or missing enum variant name", Some(*span))
+            }
+            ParserError::EnumVariantValue(span) => ("invalid enum variant value", Some(*span)),
+            ParserError::UnionName(span) => ("invalid or missing union name", Some(*span)),
+            ParserError::UnionVariantName(span) => {
+                ("invalid or missing union variant name", Some(*span))
+            }
+            ParserError::UnionVariantDataType(span) => {
+                ("invalid or missing union variant type", Some(*span))
+            }
+            ParserError::InterfaceName(span) => ("invalid or missing interface name", Some(*span)),
+            ParserError::FunctionName(span) => ("invalid or missing function name", Some(*span)),
+            ParserError::FieldDataType(span) => ("invalid or missing field type", Some(*span)),
+            ParserError::Literal(span) => ("invalid literal", Some(*span)),
+            ParserError::DataType(span) => ("invalid type", Some(*span)),
+            ParserError::DataTypeContainerName(span) => ("invalid container", Some(*span)),
+            ParserError::DataTypeContainerInnerType(span) => {
+                ("invalid container inner type", Some(*span))
+            }
+            ParserError::DataTypeContainerArity(span) => {
+                ("wrong number of types in container", Some(*span))
+            }
+            ParserError::DataTypeArraySize(span) => ("invalid array size", Some(*span)),
+            ParserError::FunctionArgument(span) => {
+                ("invalid or missing function argument", Some(*span))
+            }
+            ParserError::FunctionArgumentDataType(span) => {
+                ("invalid or missing function argument type", Some(*span))
+            }
+        };
+
+        let help = match error {
+            ParserError::Literal(_) => Some(
+                "integers must fit in an `i128`, including `u128` defaults, and floats in an \
+                     `f64`"
+                    .into(),
+            ),
+            ParserError::EnumVariantValue(_) => {
+                Some("enum values must fit in a `u16`, from 0 to 65535".into())
+            }
+            ParserError::DataTypeContainerArity(_) => Some(
+                "`map` holds a key and a value type, `tuple` holds 2 to 12 types, other \
+                     containers hold one type"
+                    .into(),
+            ),
+            _ => None,
+        };
+
+        Diagnostic {
+            severity: Severity::Error,
+            message: message.to_string(),
+            span,
+            help,
+        }
+    }
+}
+
+impl From<&ValidationError> for Diagnostic {
+    fn from(error: &ValidationError) -> Self {
+        let (message, help) = match error {
+            ValidationError::ImportedModuleNotFound { import, .. } => (
+                format!("module `{import}` was not found in the workspace"),
+                Some(format!(
+                    "check that `{import}` is spelled correctly and is part of the workspace"
+                )),
+            ),
+            ValidationError::IndirectDependency {
+                import, package, ..
+            } => (
+                format!("module `{import}` belongs to `{package}`, which is not a dependency"),
+                Some(format!(
+                    "add `{package}` to the `[dependencies]` of the package to import its modules"
+                )),
+            ),
+            ValidationError::DependencyShadowed { module, package, .. } => (
+                format!("module `{module}` shadows the dependency `{package}`"),
+                Some(format!(
+                    "imports starting with `{package}` resolve to the dependency, rename the module"
+                )),
+            ),
+            ValidationError::SelfImport { module, .. } => (
+                format!("module `{module}` imports itself"),
+                Some("types of the module can be used without importing it".into()),
+            ),
+            ValidationError::DuplicateImport { import, first, .. } => (
+                format!("module `{import}` is imported more than once"),
+                Some(format!("`{import}` is first imported at {first}")),
+            ),
+            ValidationError::UnusedImport { import, .. } => (
+                format!("module `{import}` is imported but never used"),
+                Some("remove the import".into()),
+            ),
+            ValidationError::UnusedImportedType { name, import, .. } => (
+                format!("type `{name}` is imported from `{import}` but never used"),
+                Some(format!("remove `{name}` from the import")),
+            ),
+            ValidationError::ImportedTypeNotFound { name, import, .. } => (
+                format!("type `{name}` was not found in `{import}`"),
+                Some(format!("check that `{name}` is declared in `{import}`")),
+            ),
+            ValidationError::ImportConflict { name, first, .. } => (
+                format!("`{name}` is imported, but the name is already used"),
+                Some(format!(
+                    "`{name}` is first used at {first}, import its module with an alias instead"
+                )),
+            ),
+            ValidationError::ModuleNotImported {
+                import, type_name, ..
+            } => (
+                format!("type `{type_name}` belongs to module `{import}`, which is not imported"),
+                Some(format!("did you forget `import {import};`?")),
+            ),
+            ValidationError::TypeNotFound { type_name, .. } => {
+                let help = match type_name.rsplit_once('.') {
+                    Some((import, name)) => {
+                        format!("check that `{name}` is declared in `{import}`")
+                    }
+                    None => format!(
+                        "declare `{type_name}` in this module, or import the module declaring it"
+                    ),
+                };
+                (format!("type `{type_name}` was not found"), Some(help))
+            }
+            ValidationError::InterfaceAsType { type_name, .. } => (
+                format!("interface `{type_name}` cannot be used as a data type"),
+                Some(
+                    "only schemas, enums and unions can be used as field, argument and return types"
+                        .into(),
+                ),
+            ),
+            ValidationError::InvalidKeyType { type_name, .. } => (
+                format!("`{type_name}` cannot be used as a map key or a set element"),
+                Some(
+                    "map keys and set elements must be enums or primitives other than floats"
+                        .into(),
+                ),
+            ),
+            ValidationError::InvalidArraySize { type_name, .. } => (
+                format!("invalid size for `{type_name}`"),
+                Some("arrays hold from 1 to 32 elements, use `vec` for larger sequences".into()),
+            ),
+            ValidationError::InvalidDefault {
+                type_name, literal, ..
+            } => (
+                format!("`{literal}` is not a valid `{type_name}`"),
+                Some(
+                    "defaults are supported for numbers, `bool`, `str` and `option` fields, \
+                     which also accept `none`"
+                        .into(),
+                ),
+            ),
+            ValidationError::MissingDefault {
+                definition,
+                field,
+                type_name,
+                ..
+            } => (
+                format!("`{definition}` gets no `Default` implementation"),
+                Some(format!(
+                    "`{field}` is a `{type_name}`, which has no default, give it a default value"
+                )),
+            ),
+            ValidationError::RecursiveSchema {
+                definition, cycle, ..
+            } => (
+                format!("`{definition}` contains itself: {cycle}"),
+                Some("wrap one of the references in `option` or `vec` to break the cycle".into()),
+            ),
+            ValidationError::DuplicateDefinition { name, first, .. } => (
+                format!("`{name}` is defined more than once in this module"),
+                Some(format!("`{name}` is first defined at {first}")),
+            ),
+            ValidationError::DuplicateField {
+                definition,
+                name,
+                first,
+                ..
+            } => (
+                format!("field `{name}` is declared more than once in `{definition}`"),
+                Some(format!("`{name}` is first declared at {first}")),
+            ),
+            ValidationError::DuplicateVariant {
+                definition,
+                name,
+                first,
+                ..
+            } => (
+                format!("variant `{name}` is declared more than once in `{definition}`"),
+                Some(format!("`{name}` is first declared at {first}")),
+            ),
+            ValidationError::DuplicateFunction {
+                definition,
+                name,
+                first,
+                ..
+            } => (
+                format!("function `{name}` is declared more than once in `{definition}`"),
+                Some(format!("`{name}` is first declared at {first}")),
+            ),
+            ValidationError::UnknownAnnotation { name, .. } => (
+                format!("unknown annotation `@{name}`"),
+                Some(
+                    "built-in annotations are `@deprecated` and `@idempotent`, others must be \
+                     handled by a code generator"
+                        .into(),
+                ),
+            ),
+            ValidationError::InvalidAnnotation { name, reason, .. } => {
+                (format!("invalid annotation `@{name}`: {reason}"), None)
+            }
+            ValidationError::ReservedVariant {
+                definition,
+                name,
+                reason,
+                ..
+            } => (
+                format!("variant `{name}` of `{definition}` is reserved, {reason}"),
+                Some(format!("rename `{name}`")),
+            ),
+            ValidationError::InvalidEnumValue { name, value, .. } => (
+                format!("value {value} of variant `{name}` does not fit in a `u16`"),
+                Some("enum values must be from 0 to 65535".into()),
+            ),
+            ValidationError::DuplicateEnumValue {
+                definition,
+                name,
+                value,
+                first,
+                ..
+            } => (
+                format!("value {value} of variant `{name}` is already used in `{definition}`"),
+                Some(format!("the value {value} is first used at {first}")),
+            ),
+        };
+
+        Diagnostic {
+            severity: error.severity(),
+            message,
+            span: Some(error.span()),
+            help,
+        }
+    }
+}
+
+/// Builds a [Diagnostic] from a pest error, renaming grammar rules to Misty terminology.
+fn pest_diagnostic(error: &PestError<Rule>) -> Diagnostic {
+    let error = error.clone().renamed_rules(describe_rule);
+
+    let (start, end) = match error.location {
+        InputLocation::Pos(position) => (position, position),
+        InputLocation::Span(span) => span,
+    };
+    let (line, column) = match error.line_col {
+        LineColLocation::Pos(position) => position,
+        LineColLocation::Span(start, _) => start,
+    };
+
+    Diagnostic {
+        severity: Severity::Error,
+        message: error.variant.message().to_string(),
+        span: Some(Span {
+            start,
+            end,
+            line,
+            column,
+        }),
+        help: None,
+    }
+}
+
+/// Describes a grammar rule the way it's referred to in Misty.
+fn describe_rule(rule: &Rule) -> String {
+    let description = match rule {
+        Rule::EOI => "end of file",
+        Rule::WHITESPACE => "whitespace",
+        Rule::COMMENT => "comment",
+        Rule::block_comment => "block comment",
+        Rule::doc_comment => "doc comment",
+        Rule::doc_target => "documented item",
+        Rule::IMPORT_KW => "`import`",
+        Rule::INTERFACE_KW => "`interface`",
+        Rule::SCHEMA_KW => "`schema`",
+        Rule::ENUM_KW => "`enum`",
+        Rule::UNION_KW => "`union`",
+        Rule::FN_KW => "`fn`",
+        Rule::AS_KW => "`as`",
+        Rule::STREAM_KW => "`stream`",
+        Rule::ident_pascal => "PascalCase name",
+        Rule::ident_snake => "snake_case name",
+        Rule::ident_scream => "SCREAMING_SNAKE_CASE name",
+        Rule::primitive => "primitive type",
+        Rule::user_type => "type name",
+        Rule::container_type => "container name",
+        Rule::array_size => "array size",
+        Rule::array => "array type",
+        Rule::container => "container type",
+        Rule::field_type => "field type",
+        Rule::import_alias => "import alias",
+        Rule::import_items => "imported types",
+        Rule::import_stmt => "import",
+        Rule::annotation_arg => "annotation argument",
+        Rule::annotation => "annotation",
+        Rule::enum_value => "enum variant value",
+        Rule::enum_variant => "enum variant",
+        Rule::enum_def => "enum",
+        Rule::literal_int => "integer",
+        Rule::literal_float => "float",
+        Rule::literal_bool => "boolean",
+        Rule::literal_str => "string",
+        Rule::literal_none => "`none`",
+        Rule::literal => "default value",
+        Rule::field_def => "field",
+        Rule::schema_def => "schema",
+        Rule::union_variant => "union variant",
+        Rule::union_def => "union",
+        Rule::fn_arg => "function argument",
+        Rule::fn_return => "function return type",
+        Rule::fn_def => "function",
+        Rule::interface_def => "interface",
+        Rule::file => "definition",
+    };
+
+    description.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Diagnostic, Workspace, parse};
+
+    #[test]
+    fn it_renders_code_frames() {
+        let source = "schema User {\n    tags: vec<Tag>;\n}\n";
+        let mut workspace = Workspace::new();
+        workspace.add_local_module("users", parse(source).unwrap());
+
+        let errors = workspace.validate_all().unwrap_err();
+        let report = Diagnostic::from(&errors[0]).render("users.misty", source);
+        assert_eq!(
+            report,
+            "error: type `Tag` was not found\n \
+             --> users.misty:2:5\n  \
+             |\n\
+             2 |     tags: vec<Tag>;\n  \
+             |     ^^^^^^^^^^^^^^^\n  \
+             |\n  \
+             = help: declare `Tag` in this module, or import the module declaring it\n"
+        );
+    }
+
+    #[test]
+    fn it_renames_grammar_rules() {
+        let source = "schema User {\n    name: ;\n}\n";
+        let Err(error) = parse(source) else {
+            panic!("expected a parser error");
+        };
+
+        let diagnostic = Diagnostic::from(&error);
+        assert_eq!(diagnostic.message, "expected field type");
+        assert_eq!(
+            diagnostic.span.map(|span| (span.line, span.column)),
+            Some((2, 11))
+        );
+    }
+}
@@ -1,7 +1,10 @@
 use crate::pest_parser::Rule;
 use crate::validator::ValidationError;
+use misty_ast::Span;
 
 /// General parser errors.
+///
+/// Errors raised while building AST nodes carry the [Span] of the node that was being parsed.
 #[derive(Debug, thiserror::Error)]
 pub enum ParserError {
     #[error("Pest Parser Error: {0}")]
@@ -10,39 +13,69 @@ pub enum ParserError {
     #[error("No root AST node was found in the source")]
     RootNode,
 
-    #[error("The Enum name is invalid or is missing")]
-    EnumName,
+    #[error("The Import is invalid at {0}")]
+    Import(Span),
 
-    #[error("The Schema name is invalid or is missing")]
-    SchemaName,
+    #[error("The Annotation name is invalid or is missing at {0}")]
+    AnnotationName(Span),
 
-    #[error("The Schema Field name is invalid or is missing")]
-    FieldName,
+    #[error("The Enum name is invalid or is missing at {0}")]
+    EnumName(Span),
 
-    #[error("The Interface name is invalid or is missing")]
-    InterfaceName,
+    #[error("The Enum Variant name is invalid or is missing at {0}")]
+    EnumVariantName(Span),
 
-    #[error("The Function name is invalid or is missing")]
-    FunctionName,
+    #[error("The Enum Variant value is invalid at {0}")]
+    EnumVariantValue(Span),
 
-    #[error("The Schema Field type is invalid or is missing")]
-    FieldDataType,
+    #[error("The Union name is invalid or is missing at {0}")]
+    UnionName(Span),
 
-    #[error("The DataType declared is invalid")]
-    DataType,
+    #[error("The Union Variant name is invalid or is missing at {0}")]
+    UnionVariantName(Span),
 
-    #[error("The DataType container name is invalid")]
-    DataTypeContainerName,
+    #[error("The Union Variant type is invalid or is missing at {0}")]
+    UnionVariantDataType(Span),
 
-    #[error("The DataType container inner type is invalid")]
-    DataTypeContainerInnerType,
+    #[error("The Schema name is invalid or is missing at {0}")]
+    SchemaName(Span),
 
-    #[error("The Function argument is invalid or is missing")]
-    FunctionArgument,
+    #[error("The Schema Field name is invalid or is missing at {0}")]
+    FieldName(Span),
 
-    #[error("The Function Argument type is invalid or is missing")]
-    FunctionArgumentDataType,
+    #[error("The Interface name is invalid or is missing at {0}")]
+    InterfaceName(Span),
+
+    #[error("The Function name is invalid or is missing at {0}")]
+    FunctionName(Span),
+
+    #[error("The Schema Field type is invalid or is missing at {0}")]
+    FieldDataType(Span),
+
+    #[error("The Literal is invalid at {0}")]
+    Literal(Span),
+
+    #[error("The DataType declared is invalid at {0}")]
+    DataType(Span),
+
+    #[error("The DataType container name is invalid at {0}")]
+    DataTypeContainerName(Span),
+
+    #[error("The DataType container inner type is invalid at {0}")]
+    DataTypeContainerInnerType(Span),
+
+    #[error("The DataType container holds the wrong number of inner types at {0}")]
+    DataTypeContainerArity(Span),
+
+    #[error("The DataType array size is invalid at {0}")]
+    DataTypeArraySize(Span),
+
+    #[error("The Function argument is invalid or is missing at {0}")]
+    FunctionArgument(Span),
+
+    #[error("The Function Argument type is invalid or is missing at {0}")]
+    FunctionArgumentDataType(Span),
 
     #[error("Validation Error: {0}")]
-    Validation(#[from] ValidationError),
+    Validation(#[from] Box<ValidationError>),
 }
@@ -1,8 +1,16 @@
 mod ast;
+mod cst;
+mod diagnostic;
 mod error;
+mod loader;
 mod pest_parser;
 mod validator;
 
 pub use ast::parse;
+pub use cst::{NodeKind, SyntaxElement, SyntaxNode, SyntaxToken, TokenKind, parse_cst};
+pub use diagnostic::Diagnostic;
 pub use error::ParserError;
-pub use validator::Workspace;
+pub use loader::{
+    LoadError, MANIFEST_FILE, MISTY_EXTENSION, SourceFile, find_misty_files, is_snake_case,
+};
+pub use validator::{Package, Severity, ValidationError, ValidationErrorKind, Workspace};
@@ -0,0 +1,30 @@
+use crate::ParserError;
+use crate::loader::SourceFile;
+use std::path::PathBuf;
+
+/// Errors raised while loading the Misty files of a directory.
+#[derive(Debug, thiserror::Error)]
+pub enum LoadError {
+    #[error("Failed to read `{path}`: {source}")]
+    Read {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("The file `{path}` has no module path, `{segment}` is not a snake_case name")]
+    ModulePath { path: PathBuf, segment: String },
+
+    #[error("The module `{module}` is declared by both `{first}` and `{second}`")]
+    DuplicateModule {
+        module: String,
+        first: PathBuf,
+        second: PathBuf,
+    },
+
+    #[error("Failed to parse `{}`: {error}", .file.path.display())]
+    Parse {
+        file: SourceFile,
+        #[source]
+        error: Box<ParserError>,
+    },
+}
@@ -0,0 +1,114 @@
+//! The loader module contains the logic to discover the Misty files of a directory.
+//!
+//! Every `.misty` file of the directory and its subdirectories is a module, whose path is derived
+//! from the path of the file relative to the directory: each directory is a segment of the module
+//! path, so `ml/analyzer.misty` declares `ml.analyzer`. Like module names written in imports, each
+//! segment must be a snake_case name.
+//!
+//! Subdirectories holding a `Misty.toml` manifest are other packages, like vendored dependencies,
+//! and are skipped.
+//!
+//! See [Workspace::load_dir](crate::Workspace::load_dir) to load every module into a workspace.
+mod error;
+
+pub use error::LoadError;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Extension of Misty files.
+pub const MISTY_EXTENSION: &str = "misty";
+
+/// Name of the manifest file, at the root of a package.
+pub const MANIFEST_FILE: &str = "Misty.toml";
+
+/// A Misty file loaded into a workspace.
+#[derive(Debug, Clone)]
+pub struct SourceFile {
+    /// Path of the file.
+    pub path: PathBuf,
+
+    /// Content of the file.
+    pub source: String,
+}
+
+/// Finds the Misty files of a directory and its subdirectories, sorted.
+///
+/// Hidden directories, like `.git`, the directories of other packages and symlinks are skipped.
+pub fn find_misty_files(dir: &Path) -> Result<Vec<PathBuf>, LoadError> {
+    let mut files = Vec::new();
+    visit_dir(dir, &mut files)?;
+    files.sort();
+
+    Ok(files)
+}
+
+/// Helper function to collect the Misty files of a directory and its subdirectories.
+fn visit_dir(dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), LoadError> {
+    let read_error = |source| LoadError::Read {
+        path: dir.to_path_buf(),
+        source,
+    };
+
+    for entry in fs::read_dir(dir).map_err(read_error)? {
+        let entry = entry.map_err(read_error)?;
+        let file_type = entry.file_type().map_err(read_error)?;
+        let path = entry.path();
+        let hidden = path
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().starts_with('.'));
+
+        // Symlinks could point back to a parent directory, or out of the package.
+        if file_type.is_symlink() {
+            continue;
+        }
+
+        if file_type.is_dir() {
+            if !hidden && !path.join(MANIFEST_FILE).exists() {
+                visit_dir(&path, files)?;
+            }
+        } else if path
+            .extension()
+            .is_some_and(|extension| extension == MISTY_EXTENSION)
+        {
+            files.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+/// Path of the module declared by a Misty file, relative to the root directory.
+///
+/// Every directory is a segment of the module path: `ml/analyzer.misty` declares `ml.analyzer`.
+/// Segments that are not snake_case names are rejected.
+pub fn module_path(root: &Path, file: &Path) -> Result<String, LoadError> {
+    let relative = file.strip_prefix(root).unwrap_or(file).with_extension("");
+
+    let mut segments = Vec::new();
+    for component in relative.components() {
+        let segment = component.as_os_str().to_string_lossy();
+        if !is_snake_case(&segment) {
+            tracing::debug!(?file, %segment, "Invalid module path segment");
+            return Err(LoadError::ModulePath {
+                path: file.to_path_buf(),
+                segment: segment.into_owned(),
+            });
+        }
+        segments.push(segment);
+    }
+
+    Ok(segments.join("."))
+}
+
+/// Checks if a name is snake_case: lowercase letters, digits and underscores, starting with a
+/// letter.
+///
+/// Module path segments and package names are used as directory and file names, so they must be
+/// checked before being used as such.
+pub fn is_snake_case(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_lowercase())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
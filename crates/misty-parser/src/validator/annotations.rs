@@ -0,0 +1,149 @@
+use crate::validator::ValidationError;
+use crate::validator::scope::ModuleScope;
+use misty_ast::{Annotation, Definition, Literal};
+use std::collections::HashSet;
+use std::fmt;
+
+/// Kinds of nodes annotations can be attached to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AnnotationTarget {
+    Schema,
+    Field,
+    Enum,
+    EnumVariant,
+    Union,
+    UnionVariant,
+    Interface,
+    Function,
+}
+
+impl fmt::Display for AnnotationTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            AnnotationTarget::Schema => "schemas",
+            AnnotationTarget::Field => "fields",
+            AnnotationTarget::Enum => "enums",
+            AnnotationTarget::EnumVariant => "enum variants",
+            AnnotationTarget::Union => "unions",
+            AnnotationTarget::UnionVariant => "union variants",
+            AnnotationTarget::Interface => "interfaces",
+            AnnotationTarget::Function => "functions",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// Checks the annotations of every definition and member in a module.
+///
+/// The built-in annotations are validated against their target and arguments:
+/// - `@deprecated` is allowed everywhere, with an optional reason: `@deprecated("use v2")`.
+/// - `@idempotent` is only allowed on functions, without arguments.
+///
+/// Annotations named after one of the `namespaces` registered by code generators, like `@rust`,
+/// are passed through as-is. Every other annotation is reported as unknown.
+pub fn check_annotations(
+    scope: &ModuleScope,
+    namespaces: &HashSet<String>,
+    errors: &mut Vec<ValidationError>,
+) {
+    let mut check = |definition: &str, target, annotations: &[Annotation]| {
+        attest_annotations(scope, namespaces, definition, target, annotations, errors)
+    };
+
+    for definition in &scope.file.definitions {
+        let name = definition.name();
+        match definition {
+            Definition::Schema(schema) => {
+                check(name, AnnotationTarget::Schema, &schema.annotations);
+                for field in &schema.fields {
+                    check(name, AnnotationTarget::Field, &field.annotations);
+                }
+            }
+            Definition::Enum(misty_enum) => {
+                check(name, AnnotationTarget::Enum, &misty_enum.annotations);
+                for variant in &misty_enum.variants {
+                    check(name, AnnotationTarget::EnumVariant, &variant.annotations);
+                }
+            }
+            Definition::Union(union) => {
+                check(name, AnnotationTarget::Union, &union.annotations);
+                for variant in &union.variants {
+                    check(name, AnnotationTarget::UnionVariant, &variant.annotations);
+                }
+            }
+            Definition::Interface(interface) => {
+                check(name, AnnotationTarget::Interface, &interface.annotations);
+                for function in &interface.functions {
+                    check(name, AnnotationTarget::Function, &function.annotations);
+                }
+            }
+        }
+    }
+}
+
+/// Helper method to check the annotations attached to a single node.
+fn attest_annotations(
+    scope: &ModuleScope,
+    namespaces: &HashSet<String>,
+    definition: &str,
+    target: AnnotationTarget,
+    annotations: &[Annotation],
+    errors: &mut Vec<ValidationError>,
+) {
+    let mut seen = HashSet::new();
+    for annotation in annotations {
+        let name = annotation.name.as_str();
+        let invalid = |reason: String| ValidationError::InvalidAnnotation {
+            module: scope.module_path.to_string(),
+            definition: definition.to_string(),
+            name: name.to_string(),
+            reason,
+            span: annotation.span,
+        };
+
+        // Passthrough annotations can be repeated, like `@rust(...)` with different arguments.
+        if namespaces.contains(name) {
+            continue;
+        }
+
+        if !seen.insert(name) {
+            tracing::debug!(?name, "Repeated annotation");
+            errors.push(invalid("it can only be used once per item".into()));
+            continue;
+        }
+
+        match name {
+            "deprecated" => {
+                let valid = match annotation.arguments.as_slice() {
+                    [] => true,
+                    [argument] => {
+                        argument.key.is_none() && matches!(argument.value, Literal::Str(_))
+                    }
+                    _ => false,
+                };
+                if !valid {
+                    tracing::debug!(?name, "Invalid annotation arguments");
+                    errors.push(invalid("it takes an optional reason, as a string".into()));
+                }
+            }
+            "idempotent" => {
+                if target != AnnotationTarget::Function {
+                    tracing::debug!(?name, %target, "Invalid annotation target");
+                    errors.push(invalid(format!("it cannot be used on {target}")));
+                } else if !annotation.arguments.is_empty() {
+                    tracing::debug!(?name, "Invalid annotation arguments");
+                    errors.push(invalid("it takes no arguments".into()));
+                }
+            }
+            _ => {
+                tracing::debug!(?name, "Unknown annotation");
+                errors.push(ValidationError::UnknownAnnotation {
+                    module: scope.module_path.to_string(),
+                    definition: definition.to_string(),
+                    name: name.to_string(),
+                    span: annotation.span,
+                });
+            }
+        }
+    }
+}
@@ -0,0 +1,338 @@
+//! Detection of schemas containing themselves.
+//!
+//! Schemas reference each other by value, so a schema containing itself, directly or through other
+//! schemas, would have an infinite size. Unions hold the value of their variants inline too, so
+//! they take part in cycles the same way schemas do.
+//!
+//! Containers storing their elements on the heap, like `vec`, break such cycles. An `option` breaks
+//! them too, as long as the code generators store the recursive value behind an indirection: those
+//! references are reported by [recursive_references].
+//!
+//! Only the local modules are part of the graph: dependencies never reference the modules of the
+//! packages depending on them, so a cycle can't go through them.
+use crate::Workspace;
+use crate::validator::ValidationError;
+use crate::validator::scope::ModuleScope;
+use misty_ast::{ContainerType, DataType, Definition, Span};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+
+/// A schema or a union, identified by its module path and name.
+type SchemaId<'a> = (&'a str, &'a str);
+
+/// A reference stored by value from a schema field or union variant to another schema or union.
+struct Reference<'a> {
+    /// The referenced schema or union.
+    target: SchemaId<'a>,
+
+    /// The field or variant holding the reference.
+    field: &'a str,
+
+    /// The reference as written in the source.
+    type_name: &'a str,
+
+    /// Whether the reference is wrapped in an `option`.
+    optional: bool,
+
+    /// Location of the field or variant holding the reference.
+    span: Span,
+}
+
+/// Graph of the by-value references between every schema and union of the workspace.
+struct SchemaGraph<'a> {
+    references: BTreeMap<SchemaId<'a>, Vec<Reference<'a>>>,
+
+    /// The unions of the graph, every other node is a schema.
+    unions: HashSet<SchemaId<'a>>,
+}
+
+impl<'a> SchemaGraph<'a> {
+    /// Builds the graph from every module of the workspace.
+    ///
+    /// References that do not resolve to a schema or a union are skipped, they are reported by the
+    /// type resolver.
+    fn build(workspace: &'a Workspace) -> Self {
+        let mut references = BTreeMap::new();
+        let mut unions = HashSet::new();
+
+        for (module_path, file) in workspace.package_local_modules() {
+            let scope = ModuleScope {
+                workspace,
+                module_path,
+                file,
+            };
+
+            for definition in &file.definitions {
+                // Members holding a value, along with their name and location.
+                let members = match definition {
+                    Definition::Schema(schema) => schema
+                        .fields
+                        .iter()
+                        .map(|field| (&*field.name, &field.field_type, field.span))
+                        .collect::<Vec<_>>(),
+                    Definition::Union(union) => {
+                        unions.insert((module_path.as_str(), &*union.name));
+                        union
+                            .variants
+                            .iter()
+                            .map(|variant| (&*variant.name, &variant.variant_type, variant.span))
+                            .collect()
+                    }
+                    Definition::Enum(_) | Definition::Interface(_) => continue,
+                };
+
+                let mut schema_references = Vec::new();
+                for (member, data_type, span) in members {
+                    let mut type_names = Vec::new();
+                    collect_inline_types(data_type, false, &mut type_names);
+
+                    for (type_name, optional) in type_names {
+                        if !matches!(
+                            scope.find_definition(type_name),
+                            Some(Definition::Schema(_) | Definition::Union(_))
+                        ) {
+                            continue;
+                        }
+                        let target = scope.qualify(type_name);
+
+                        schema_references.push(Reference {
+                            target,
+                            field: member,
+                            type_name,
+                            optional,
+                            span,
+                        });
+                    }
+                }
+                references.insert((module_path.as_str(), definition.name()), schema_references);
+            }
+        }
+
+        Self { references, unions }
+    }
+
+    /// Computes the strongly connected components of the graph with Tarjan's algorithm.
+    ///
+    /// Only components that actually contain a cycle are returned. Optional references are
+    /// followed only when `follow_optional` is set.
+    fn cycles(&self, follow_optional: bool) -> Vec<Vec<SchemaId<'a>>> {
+        let mut tarjan = Tarjan {
+            graph: self,
+            follow_optional,
+            index: 0,
+            indices: HashMap::new(),
+            low_links: HashMap::new(),
+            stack: Vec::new(),
+            on_stack: HashSet::new(),
+            components: Vec::new(),
+        };
+        for &schema in self.references.keys() {
+            if !tarjan.indices.contains_key(&schema) {
+                tarjan.visit(schema);
+            }
+        }
+
+        // Single schemas are only a cycle when they reference themselves.
+        tarjan
+            .components
+            .into_iter()
+            .filter(|component| {
+                component.len() > 1
+                    || self
+                        .edges(component[0], follow_optional)
+                        .any(|reference| reference.target == component[0])
+            })
+            .collect()
+    }
+
+    /// References leaving a schema, optionally skipping the optional ones.
+    fn edges(
+        &self,
+        schema: SchemaId<'a>,
+        follow_optional: bool,
+    ) -> impl Iterator<Item = &Reference<'a>> {
+        self.references
+            .get(&schema)
+            .into_iter()
+            .flatten()
+            .filter(move |reference| follow_optional || !reference.optional)
+    }
+}
+
+/// State of Tarjan's strongly connected components algorithm.
+struct Tarjan<'g, 'a> {
+    graph: &'g SchemaGraph<'a>,
+    follow_optional: bool,
+    index: usize,
+    indices: HashMap<SchemaId<'a>, usize>,
+    low_links: HashMap<SchemaId<'a>, usize>,
+    stack: Vec<SchemaId<'a>>,
+    on_stack: HashSet<SchemaId<'a>>,
+    components: Vec<Vec<SchemaId<'a>>>,
+}
+
+impl<'a> Tarjan<'_, 'a> {
+    fn visit(&mut self, schema: SchemaId<'a>) {
+        self.indices.insert(schema, self.index);
+        self.low_links.insert(schema, self.index);
+        self.index += 1;
+        self.stack.push(schema);
+        self.on_stack.insert(schema);
+
+        let targets = self
+            .graph
+            .edges(schema, self.follow_optional)
+            .map(|reference| reference.target)
+            .collect::<Vec<_>>();
+        for target in targets {
+            if !self.indices.contains_key(&target) {
+                self.visit(target);
+                let low_link = self.low_links[&schema].min(self.low_links[&target]);
+                self.low_links.insert(schema, low_link);
+            } else if self.on_stack.contains(&target) {
+                let low_link = self.low_links[&schema].min(self.indices[&target]);
+                self.low_links.insert(schema, low_link);
+            }
+        }
+
+        // The schema is the root of a component, pop the whole component from the stack.
+        if self.low_links[&schema] == self.indices[&schema] {
+            let mut component = Vec::new();
+            while let Some(member) = self.stack.pop() {
+                self.on_stack.remove(&member);
+                component.push(member);
+                if member == schema {
+                    break;
+                }
+            }
+            component.sort();
+            self.components.push(component);
+        }
+    }
+}
+
+/// Checks that no schema or union contains itself by value.
+///
+/// Each group of schemas containing each other is reported once, with the shortest cycle going
+/// through its first schema (by module path, then name).
+pub fn check_cycles(workspace: &Workspace, errors: &mut Vec<ValidationError>) {
+    let graph = SchemaGraph::build(workspace);
+
+    for component in graph.cycles(false) {
+        let start = component[0];
+        let cycle = shortest_cycle(&graph, start, &component);
+        let Some((_, reference)) = cycle.first() else {
+            continue;
+        };
+
+        let path = std::iter::once(start)
+            .chain(cycle.iter().map(|(_, reference)| reference.target))
+            .map(|(module, name)| format!("{module}.{name}"))
+            .collect::<Vec<_>>()
+            .join(" -> ");
+        tracing::debug!(?path, "Schema contains itself");
+        let definition_kind = if graph.unions.contains(&start) {
+            "union"
+        } else {
+            "schema"
+        };
+        errors.push(ValidationError::RecursiveSchema {
+            module: start.0.to_string(),
+            definition_kind,
+            definition: start.1.to_string(),
+            field: reference.field.to_string(),
+            type_name: reference.type_name.to_string(),
+            cycle: path,
+            span: reference.span,
+        });
+    }
+}
+
+/// Finds the shortest cycle of by-value references going through `start`, inside a component.
+///
+/// Returns each step of the cycle as the referencing schema and the reference it follows, starting
+/// at `start`.
+fn shortest_cycle<'g, 'a>(
+    graph: &'g SchemaGraph<'a>,
+    start: SchemaId<'a>,
+    component: &[SchemaId<'a>],
+) -> Vec<(SchemaId<'a>, &'g Reference<'a>)> {
+    // Breadth-first search from the start, recording how each schema was first reached.
+    let mut previous = HashMap::new();
+    let mut queue = VecDeque::from([start]);
+    while let Some(schema) = queue.pop_front() {
+        for reference in graph.edges(schema, false) {
+            let target = reference.target;
+            if !component.contains(&target) || previous.contains_key(&target) {
+                continue;
+            }
+            previous.insert(target, (schema, reference));
+
+            // Back at the start, walk the recorded steps backwards to rebuild the cycle.
+            if target == start {
+                let mut cycle = Vec::new();
+                let mut current = start;
+                loop {
+                    let (schema, reference) = previous[&current];
+                    cycle.push((schema, reference));
+                    current = schema;
+                    if current == start {
+                        break;
+                    }
+                }
+                cycle.reverse();
+                return cycle;
+            }
+            queue.push_back(target);
+        }
+    }
+
+    Vec::new()
+}
+
+/// Finds every reference that must be stored behind an indirection.
+///
+/// These are the optional references taking part in a cycle. Each one is returned as the module
+/// path and name of the referencing schema, along with the reference as written in the source.
+pub fn recursive_references(workspace: &Workspace) -> HashSet<(String, String, String)> {
+    let graph = SchemaGraph::build(workspace);
+    let mut recursive = HashSet::new();
+
+    for component in graph.cycles(true) {
+        let members = component.iter().copied().collect::<HashSet<_>>();
+        for &schema in &component {
+            for reference in graph.edges(schema, true) {
+                if reference.optional && members.contains(&reference.target) {
+                    recursive.insert((
+                        schema.0.to_string(),
+                        schema.1.to_string(),
+                        reference.type_name.to_string(),
+                    ));
+                }
+            }
+        }
+    }
+
+    recursive
+}
+
+/// Collects every user type stored by value in a [DataType], along with whether it's wrapped in an
+/// `option`.
+fn collect_inline_types<'a>(
+    data_type: &'a DataType,
+    optional: bool,
+    type_names: &mut Vec<(&'a str, bool)>,
+) {
+    match data_type {
+        DataType::Primitive(_) => (),
+        DataType::UserType(type_name) => type_names.push((type_name, optional)),
+        // Vectors, maps and sets store their elements on the heap, which breaks any cycle.
+        DataType::Container(ContainerType::Vec | ContainerType::Map | ContainerType::Set, _) => (),
+        DataType::Container(ContainerType::Option, inner) => inner
+            .iter()
+            .for_each(|inner| collect_inline_types(inner, true, type_names)),
+        // Arrays and tuples store their elements inline.
+        DataType::Container(ContainerType::Array(_) | ContainerType::Tuple, inner) => inner
+            .iter()
+            .for_each(|inner| collect_inline_types(inner, optional, type_names)),
+    }
+}
@@ -0,0 +1,62 @@
+use crate::validator::ValidationError;
+use crate::validator::scope::ModuleScope;
+use misty_ast::{ContainerType, DataType, Field, Literal};
+
+/// Checks that the default value of a field, if any, is a valid value of its type.
+///
+/// Defaults are supported for numbers, `bool`, `str` and `option` fields, where `none` is also
+/// accepted. Integers must fit in the field type, and can be used for floats too. Literals are
+/// parsed as `i128`, so `u128` defaults larger than `i128::MAX` are rejected by the parser.
+pub fn attest_default(
+    scope: &ModuleScope,
+    definition: &str,
+    field: &Field,
+    errors: &mut Vec<ValidationError>,
+) {
+    let Some(default) = &field.default else {
+        return;
+    };
+
+    if !literal_fits(&field.field_type, default) {
+        tracing::debug!(?field.name, %default, "Invalid default value");
+        errors.push(ValidationError::InvalidDefault {
+            module: scope.module_path.to_string(),
+            definition: definition.to_string(),
+            field: field.name.clone(),
+            type_name: field.field_type.to_string(),
+            literal: default.to_string(),
+            span: field.span,
+        });
+    }
+}
+
+/// Checks if a literal is a valid value of a [DataType].
+fn literal_fits(data_type: &DataType, literal: &Literal) -> bool {
+    match (data_type, literal) {
+        (DataType::Container(ContainerType::Option, _), Literal::None) => true,
+        (DataType::Container(ContainerType::Option, inner), literal) => {
+            literal_fits(&inner[0], literal)
+        }
+        (DataType::Primitive(primitive), Literal::Integer(value)) => match primitive.as_str() {
+            "u8" => u8::try_from(*value).is_ok(),
+            "u16" => u16::try_from(*value).is_ok(),
+            "u32" => u32::try_from(*value).is_ok(),
+            "u64" => u64::try_from(*value).is_ok(),
+            "u128" => u128::try_from(*value).is_ok(),
+            "i8" => i8::try_from(*value).is_ok(),
+            "i16" => i16::try_from(*value).is_ok(),
+            "i32" => i32::try_from(*value).is_ok(),
+            "i64" => i64::try_from(*value).is_ok(),
+            "i128" | "f32" | "f64" => true,
+            _ => false,
+        },
+        (DataType::Primitive(primitive), Literal::Float(value)) => match primitive.as_str() {
+            "f32" => (*value as f32).is_finite(),
+            "f64" => value.is_finite(),
+            _ => false,
+        },
+        (DataType::Primitive(primitive), Literal::Bool(_)) => primitive == "bool",
+        (DataType::Primitive(primitive), Literal::Str(_)) => primitive == "str",
+        _ => false,
+    }
+}
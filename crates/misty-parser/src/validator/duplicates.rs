@@ -0,0 +1,111 @@
+use crate::validator::ValidationError;
+use crate::validator::scope::ModuleScope;
+use misty_ast::{Definition, Span};
+use std::collections::HashMap;
+
+/// Checks that every name declared in a module is unique in its namespace.
+///
+/// Definitions must be unique in their module, while fields, variants and functions must be unique
+/// in their definition. Every duplicate is reported to `errors`, along with the location of the
+/// first occurrence of its name.
+pub fn check_duplicates(scope: &ModuleScope, errors: &mut Vec<ValidationError>) {
+    let module = scope.module_path;
+
+    // Definitions share a single namespace, regardless of their kind.
+    let definitions = scope
+        .file
+        .definitions
+        .iter()
+        .map(|definition| (definition.name(), definition.span()));
+    for (name, first, second) in find_duplicates(definitions) {
+        errors.push(ValidationError::DuplicateDefinition {
+            module: module.to_string(),
+            name: name.to_string(),
+            first,
+            second,
+        });
+    }
+
+    // Members of each definition.
+    for definition in &scope.file.definitions {
+        match definition {
+            Definition::Schema(schema) => {
+                let fields = schema.fields.iter().map(|field| (&*field.name, field.span));
+                for (name, first, second) in find_duplicates(fields) {
+                    errors.push(ValidationError::DuplicateField {
+                        module: module.to_string(),
+                        definition: schema.name.clone(),
+                        name: name.to_string(),
+                        first,
+                        second,
+                    });
+                }
+            }
+            Definition::Enum(misty_enum) => {
+                let variants = misty_enum
+                    .variants
+                    .iter()
+                    .map(|variant| (&*variant.name, variant.span));
+                for (name, first, second) in find_duplicates(variants) {
+                    errors.push(ValidationError::DuplicateVariant {
+                        module: module.to_string(),
+                        definition: misty_enum.name.clone(),
+                        name: name.to_string(),
+                        first,
+                        second,
+                    });
+                }
+            }
+            Definition::Union(union) => {
+                let variants = union
+                    .variants
+                    .iter()
+                    .map(|variant| (&*variant.name, variant.span));
+                for (name, first, second) in find_duplicates(variants) {
+                    errors.push(ValidationError::DuplicateVariant {
+                        module: module.to_string(),
+                        definition: union.name.clone(),
+                        name: name.to_string(),
+                        first,
+                        second,
+                    });
+                }
+            }
+            Definition::Interface(interface) => {
+                let functions = interface
+                    .functions
+                    .iter()
+                    .map(|function| (&*function.name, function.span));
+                for (name, first, second) in find_duplicates(functions) {
+                    errors.push(ValidationError::DuplicateFunction {
+                        module: module.to_string(),
+                        definition: interface.name.clone(),
+                        name: name.to_string(),
+                        first,
+                        second,
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// Finds every name that was already declared before, in declaration order.
+///
+/// Returns each duplicate name, the location of its first declaration and the location of the
+/// duplicate.
+fn find_duplicates<'a>(names: impl Iterator<Item = (&'a str, Span)>) -> Vec<(&'a str, Span, Span)> {
+    let mut declared = HashMap::new();
+    let mut duplicates = Vec::new();
+
+    for (name, span) in names {
+        match declared.get(name) {
+            Some(first) => duplicates.push((name, *first, span)),
+            None => {
+                declared.insert(name, span);
+            }
+        }
+    }
+
+    duplicates
+}
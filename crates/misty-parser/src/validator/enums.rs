@@ -0,0 +1,48 @@
+use crate::validator::ValidationError;
+use crate::validator::scope::ModuleScope;
+use misty_ast::Definition;
+use std::collections::HashMap;
+
+/// Checks that the values of the variants of every enum in a module fit in a `u16` and are unique.
+///
+/// Enums are encoded as their value on the wire, so two variants sharing a value could not be told
+/// apart. Every error is reported to `errors`, along with the location of the first variant using
+/// the value for duplicates.
+pub fn check_enum_values(scope: &ModuleScope, errors: &mut Vec<ValidationError>) {
+    let module = scope.module_path;
+
+    for definition in &scope.file.definitions {
+        let Definition::Enum(misty_enum) = definition else {
+            continue;
+        };
+
+        let mut seen = HashMap::new();
+        for (variant, value) in misty_enum.values() {
+            if value > u64::from(u16::MAX) {
+                tracing::debug!(?variant.name, ?value, "Enum value out of range");
+                errors.push(ValidationError::InvalidEnumValue {
+                    module: module.to_string(),
+                    definition: misty_enum.name.clone(),
+                    name: variant.name.clone(),
+                    value,
+                    span: variant.span,
+                });
+                continue;
+            }
+
+            if let Some(first) = seen.get(&value) {
+                tracing::debug!(?variant.name, ?value, "Duplicate enum value");
+                errors.push(ValidationError::DuplicateEnumValue {
+                    module: module.to_string(),
+                    definition: misty_enum.name.clone(),
+                    name: variant.name.clone(),
+                    value,
+                    first: *first,
+                    second: variant.span,
+                });
+            } else {
+                seen.insert(value, variant.span);
+            }
+        }
+    }
+}
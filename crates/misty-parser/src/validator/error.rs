@@ -1,15 +1,514 @@
+use misty_ast::Span;
+
 /// Validation specific errors.
+///
+/// Every error carries the path of the module where it was found and the [Span] of the offending
+/// node inside that module. Errors raised while resolving types also carry the name of the
+/// definition declaring the type and the type as written in the source.
 #[derive(Debug, thiserror::Error)]
 pub enum ValidationError {
-    #[error("An imported module was not found in the workspace")]
-    ImportedModuleNotFound,
+    #[error("An imported module `{import}` was not found in the workspace (at {module}:{span})")]
+    ImportedModuleNotFound {
+        module: String,
+        import: String,
+        span: Span,
+    },
 
-    #[error("The module import path is invalid")]
-    ModuleImportPath,
+    #[error(
+        "The module `{import}` belongs to `{package}`, which is not a dependency of the package \
+         (at {module}:{span})"
+    )]
+    IndirectDependency {
+        module: String,
+        import: String,
+        package: String,
+        span: Span,
+    },
 
-    #[error("A required module was not imported")]
-    ModuleNotImported,
+    #[error("The module `{module}` shadows the dependency `{package}` (at {module}:{span})")]
+    DependencyShadowed {
+        module: String,
+        package: String,
+        span: Span,
+    },
+
+    #[error("The module `{module}` imports itself (at {module}:{span})")]
+    SelfImport { module: String, span: Span },
+
+    #[error("The module `{import}` is imported at {first} and again at {second} (in {module})")]
+    DuplicateImport {
+        module: String,
+        import: String,
+        first: Span,
+        second: Span,
+    },
+
+    #[error("The module `{import}` is imported but never used (at {module}:{span})")]
+    UnusedImport {
+        module: String,
+        import: String,
+        span: Span,
+    },
+
+    #[error("The type `{name}` imported from `{import}` is never used (at {module}:{span})")]
+    UnusedImportedType {
+        module: String,
+        import: String,
+        name: String,
+        span: Span,
+    },
+
+    #[error("The imported type `{name}` was not found in `{import}` (at {module}:{span})")]
+    ImportedTypeNotFound {
+        module: String,
+        import: String,
+        name: String,
+        span: Span,
+    },
+
+    #[error("The name `{name}` is used at {first} and imported again at {second} (in {module})")]
+    ImportConflict {
+        module: String,
+        name: String,
+        first: Span,
+        second: Span,
+    },
+
+    #[error("A required module `{import}` for `{type_name}` was not imported (at {module}:{span})")]
+    ModuleNotImported {
+        module: String,
+        definition: String,
+        import: String,
+        type_name: String,
+        span: Span,
+    },
+
+    #[error(
+        "A required type `{type_name}` was not found in the target module (at {module}:{span})"
+    )]
+    TypeNotFound {
+        module: String,
+        definition: String,
+        type_name: String,
+        span: Span,
+    },
+
+    #[error("The interface `{type_name}` cannot be used as a data type (at {module}:{span})")]
+    InterfaceAsType {
+        module: String,
+        definition: String,
+        type_name: String,
+        span: Span,
+    },
+
+    #[error("The key type `{type_name}` is not a primitive or an enum (at {module}:{span})")]
+    InvalidKeyType {
+        module: String,
+        definition: String,
+        type_name: String,
+        span: Span,
+    },
+
+    #[error("The array `{type_name}` must hold from 1 to 32 elements (at {module}:{span})")]
+    InvalidArraySize {
+        module: String,
+        definition: String,
+        type_name: String,
+        span: Span,
+    },
+
+    #[error(
+        "The default value `{literal}` of `{definition}.{field}` is not a valid `{type_name}` \
+         (at {module}:{span})"
+    )]
+    InvalidDefault {
+        module: String,
+        definition: String,
+        field: String,
+        type_name: String,
+        literal: String,
+        span: Span,
+    },
+
+    #[error(
+        "The schema `{definition}` gets no Default implementation, as its field `{field}` of type \
+         `{type_name}` has no default (at {module}:{span})"
+    )]
+    MissingDefault {
+        module: String,
+        definition: String,
+        field: String,
+        type_name: String,
+        span: Span,
+    },
+
+    #[error(
+        "The {definition_kind} `{definition}` contains itself through `{cycle}` \
+         (at {module}:{span})"
+    )]
+    RecursiveSchema {
+        module: String,
+        definition_kind: &'static str,
+        definition: String,
+        field: String,
+        type_name: String,
+        cycle: String,
+        span: Span,
+    },
+
+    #[error("The definition `{name}` is declared at {first} and again at {second} (in {module})")]
+    DuplicateDefinition {
+        module: String,
+        name: String,
+        first: Span,
+        second: Span,
+    },
+
+    #[error(
+        "The field `{definition}.{name}` is declared at {first} and again at {second} (in {module})"
+    )]
+    DuplicateField {
+        module: String,
+        definition: String,
+        name: String,
+        first: Span,
+        second: Span,
+    },
+
+    #[error(
+        "The variant `{definition}.{name}` is declared at {first} and again at {second} \
+         (in {module})"
+    )]
+    DuplicateVariant {
+        module: String,
+        definition: String,
+        name: String,
+        first: Span,
+        second: Span,
+    },
+
+    #[error(
+        "The function `{definition}.{name}` is declared at {first} and again at {second} \
+         (in {module})"
+    )]
+    DuplicateFunction {
+        module: String,
+        definition: String,
+        name: String,
+        first: Span,
+        second: Span,
+    },
 
-    #[error("A required imported type was not found in the target module")]
+    #[error("The annotation `@{name}` is unknown (at {module}:{span})")]
+    UnknownAnnotation {
+        module: String,
+        definition: String,
+        name: String,
+        span: Span,
+    },
+
+    #[error("The annotation `@{name}` is invalid, {reason} (at {module}:{span})")]
+    InvalidAnnotation {
+        module: String,
+        definition: String,
+        name: String,
+        reason: String,
+        span: Span,
+    },
+
+    #[error("The variant `{definition}.{name}` is reserved, {reason} (at {module}:{span})")]
+    ReservedVariant {
+        module: String,
+        definition: String,
+        name: String,
+        reason: String,
+        span: Span,
+    },
+
+    #[error(
+        "The value {value} of `{definition}.{name}` does not fit in a u16 (at {module}:{span})"
+    )]
+    InvalidEnumValue {
+        module: String,
+        definition: String,
+        name: String,
+        value: u64,
+        span: Span,
+    },
+
+    #[error(
+        "The value {value} of `{definition}.{name}` is used at {first} and again at {second} \
+         (in {module})"
+    )]
+    DuplicateEnumValue {
+        module: String,
+        definition: String,
+        name: String,
+        value: u64,
+        first: Span,
+        second: Span,
+    },
+}
+
+/// The kind of a [ValidationError], without any of its context.
+///
+/// Useful to match or report errors by category.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ValidationErrorKind {
+    ImportedModuleNotFound,
+    IndirectDependency,
+    DependencyShadowed,
+    SelfImport,
+    DuplicateImport,
+    UnusedImport,
+    UnusedImportedType,
+    ImportedTypeNotFound,
+    ImportConflict,
+    ModuleNotImported,
     TypeNotFound,
+    InterfaceAsType,
+    InvalidKeyType,
+    InvalidArraySize,
+    InvalidDefault,
+    MissingDefault,
+    RecursiveSchema,
+    DuplicateDefinition,
+    DuplicateField,
+    DuplicateVariant,
+    DuplicateFunction,
+    UnknownAnnotation,
+    InvalidAnnotation,
+    ReservedVariant,
+    InvalidEnumValue,
+    DuplicateEnumValue,
+}
+
+/// How serious a [ValidationError] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Severity {
+    /// The workspace is invalid and cannot be used to generate code.
+    Error,
+
+    /// The workspace is valid, but likely contains a mistake.
+    Warning,
+}
+
+impl ValidationError {
+    /// The kind of this error.
+    pub fn kind(&self) -> ValidationErrorKind {
+        match self {
+            ValidationError::ImportedModuleNotFound { .. } => {
+                ValidationErrorKind::ImportedModuleNotFound
+            }
+            ValidationError::IndirectDependency { .. } => ValidationErrorKind::IndirectDependency,
+            ValidationError::DependencyShadowed { .. } => ValidationErrorKind::DependencyShadowed,
+            ValidationError::SelfImport { .. } => ValidationErrorKind::SelfImport,
+            ValidationError::DuplicateImport { .. } => ValidationErrorKind::DuplicateImport,
+            ValidationError::UnusedImport { .. } => ValidationErrorKind::UnusedImport,
+            ValidationError::UnusedImportedType { .. } => ValidationErrorKind::UnusedImportedType,
+            ValidationError::ImportedTypeNotFound { .. } => {
+                ValidationErrorKind::ImportedTypeNotFound
+            }
+            ValidationError::ImportConflict { .. } => ValidationErrorKind::ImportConflict,
+            ValidationError::ModuleNotImported { .. } => ValidationErrorKind::ModuleNotImported,
+            ValidationError::TypeNotFound { .. } => ValidationErrorKind::TypeNotFound,
+            ValidationError::InterfaceAsType { .. } => ValidationErrorKind::InterfaceAsType,
+            ValidationError::InvalidKeyType { .. } => ValidationErrorKind::InvalidKeyType,
+            ValidationError::InvalidArraySize { .. } => ValidationErrorKind::InvalidArraySize,
+            ValidationError::InvalidDefault { .. } => ValidationErrorKind::InvalidDefault,
+            ValidationError::MissingDefault { .. } => ValidationErrorKind::MissingDefault,
+            ValidationError::RecursiveSchema { .. } => ValidationErrorKind::RecursiveSchema,
+            ValidationError::DuplicateDefinition { .. } => ValidationErrorKind::DuplicateDefinition,
+            ValidationError::DuplicateField { .. } => ValidationErrorKind::DuplicateField,
+            ValidationError::DuplicateVariant { .. } => ValidationErrorKind::DuplicateVariant,
+            ValidationError::DuplicateFunction { .. } => ValidationErrorKind::DuplicateFunction,
+            ValidationError::UnknownAnnotation { .. } => ValidationErrorKind::UnknownAnnotation,
+            ValidationError::InvalidAnnotation { .. } => ValidationErrorKind::InvalidAnnotation,
+            ValidationError::ReservedVariant { .. } => ValidationErrorKind::ReservedVariant,
+            ValidationError::InvalidEnumValue { .. } => ValidationErrorKind::InvalidEnumValue,
+            ValidationError::DuplicateEnumValue { .. } => ValidationErrorKind::DuplicateEnumValue,
+        }
+    }
+
+    /// Path of the module where this error was found.
+    pub fn module(&self) -> &str {
+        match self {
+            ValidationError::ImportedModuleNotFound { module, .. }
+            | ValidationError::IndirectDependency { module, .. }
+            | ValidationError::DependencyShadowed { module, .. }
+            | ValidationError::SelfImport { module, .. }
+            | ValidationError::DuplicateImport { module, .. }
+            | ValidationError::UnusedImport { module, .. }
+            | ValidationError::UnusedImportedType { module, .. }
+            | ValidationError::ImportedTypeNotFound { module, .. }
+            | ValidationError::ImportConflict { module, .. }
+            | ValidationError::ModuleNotImported { module, .. }
+            | ValidationError::TypeNotFound { module, .. }
+            | ValidationError::InterfaceAsType { module, .. }
+            | ValidationError::InvalidKeyType { module, .. }
+            | ValidationError::InvalidArraySize { module, .. }
+            | ValidationError::InvalidDefault { module, .. }
+            | ValidationError::MissingDefault { module, .. }
+            | ValidationError::RecursiveSchema { module, .. }
+            | ValidationError::DuplicateDefinition { module, .. }
+            | ValidationError::DuplicateField { module, .. }
+            | ValidationError::DuplicateVariant { module, .. }
+            | ValidationError::DuplicateFunction { module, .. }
+            | ValidationError::UnknownAnnotation { module, .. }
+            | ValidationError::InvalidAnnotation { module, .. }
+            | ValidationError::ReservedVariant { module, .. }
+            | ValidationError::InvalidEnumValue { module, .. }
+            | ValidationError::DuplicateEnumValue { module, .. } => module,
+        }
+    }
+
+    /// Mutable path of the module where this error was found.
+    fn module_mut(&mut self) -> &mut String {
+        match self {
+            ValidationError::ImportedModuleNotFound { module, .. }
+            | ValidationError::IndirectDependency { module, .. }
+            | ValidationError::DependencyShadowed { module, .. }
+            | ValidationError::SelfImport { module, .. }
+            | ValidationError::DuplicateImport { module, .. }
+            | ValidationError::UnusedImport { module, .. }
+            | ValidationError::UnusedImportedType { module, .. }
+            | ValidationError::ImportedTypeNotFound { module, .. }
+            | ValidationError::ImportConflict { module, .. }
+            | ValidationError::ModuleNotImported { module, .. }
+            | ValidationError::TypeNotFound { module, .. }
+            | ValidationError::InterfaceAsType { module, .. }
+            | ValidationError::InvalidKeyType { module, .. }
+            | ValidationError::InvalidArraySize { module, .. }
+            | ValidationError::InvalidDefault { module, .. }
+            | ValidationError::MissingDefault { module, .. }
+            | ValidationError::RecursiveSchema { module, .. }
+            | ValidationError::DuplicateDefinition { module, .. }
+            | ValidationError::DuplicateField { module, .. }
+            | ValidationError::DuplicateVariant { module, .. }
+            | ValidationError::DuplicateFunction { module, .. }
+            | ValidationError::UnknownAnnotation { module, .. }
+            | ValidationError::InvalidAnnotation { module, .. }
+            | ValidationError::ReservedVariant { module, .. }
+            | ValidationError::InvalidEnumValue { module, .. }
+            | ValidationError::DuplicateEnumValue { module, .. } => module,
+        }
+    }
+
+    /// Qualifies the module of an error found in a dependency with the name of the dependency, so
+    /// it reads like the dependency modules are imported: `common_types.users`.
+    pub(crate) fn in_dependency(mut self, dependency: &str) -> Self {
+        let module = self.module_mut();
+        *module = format!("{dependency}.{module}");
+        self
+    }
+
+    /// Name of the definition where this error was found, if it was found inside one.
+    pub fn definition(&self) -> Option<&str> {
+        match self {
+            ValidationError::ImportedModuleNotFound { .. }
+            | ValidationError::IndirectDependency { .. }
+            | ValidationError::DependencyShadowed { .. }
+            | ValidationError::SelfImport { .. }
+            | ValidationError::DuplicateImport { .. }
+            | ValidationError::UnusedImport { .. }
+            | ValidationError::UnusedImportedType { .. }
+            | ValidationError::ImportedTypeNotFound { .. }
+            | ValidationError::ImportConflict { .. } => None,
+            ValidationError::ModuleNotImported { definition, .. }
+            | ValidationError::TypeNotFound { definition, .. }
+            | ValidationError::InterfaceAsType { definition, .. }
+            | ValidationError::InvalidKeyType { definition, .. }
+            | ValidationError::InvalidArraySize { definition, .. }
+            | ValidationError::InvalidDefault { definition, .. }
+            | ValidationError::MissingDefault { definition, .. }
+            | ValidationError::RecursiveSchema { definition, .. }
+            | ValidationError::DuplicateField { definition, .. }
+            | ValidationError::DuplicateVariant { definition, .. }
+            | ValidationError::DuplicateFunction { definition, .. }
+            | ValidationError::UnknownAnnotation { definition, .. }
+            | ValidationError::InvalidAnnotation { definition, .. }
+            | ValidationError::ReservedVariant { definition, .. }
+            | ValidationError::InvalidEnumValue { definition, .. }
+            | ValidationError::DuplicateEnumValue { definition, .. } => Some(definition),
+            ValidationError::DuplicateDefinition { name, .. } => Some(name),
+        }
+    }
+
+    /// The offending type as written in the source, if this error is about a type.
+    pub fn type_name(&self) -> Option<&str> {
+        match self {
+            ValidationError::ImportedModuleNotFound { .. }
+            | ValidationError::IndirectDependency { .. }
+            | ValidationError::DependencyShadowed { .. }
+            | ValidationError::SelfImport { .. }
+            | ValidationError::DuplicateImport { .. }
+            | ValidationError::UnusedImport { .. }
+            | ValidationError::UnusedImportedType { .. }
+            | ValidationError::ImportedTypeNotFound { .. }
+            | ValidationError::ImportConflict { .. }
+            | ValidationError::DuplicateDefinition { .. }
+            | ValidationError::DuplicateField { .. }
+            | ValidationError::DuplicateVariant { .. }
+            | ValidationError::DuplicateFunction { .. }
+            | ValidationError::UnknownAnnotation { .. }
+            | ValidationError::InvalidAnnotation { .. }
+            | ValidationError::ReservedVariant { .. }
+            | ValidationError::InvalidEnumValue { .. }
+            | ValidationError::DuplicateEnumValue { .. } => None,
+            ValidationError::ModuleNotImported { type_name, .. }
+            | ValidationError::TypeNotFound { type_name, .. }
+            | ValidationError::InterfaceAsType { type_name, .. }
+            | ValidationError::InvalidKeyType { type_name, .. }
+            | ValidationError::InvalidArraySize { type_name, .. }
+            | ValidationError::InvalidDefault { type_name, .. }
+            | ValidationError::MissingDefault { type_name, .. }
+            | ValidationError::RecursiveSchema { type_name, .. } => Some(type_name),
+        }
+    }
+
+    /// Location of the offending node in its module.
+    ///
+    /// For duplicates, this is the location of the duplicate declaration.
+    pub fn span(&self) -> Span {
+        match self {
+            ValidationError::ImportedModuleNotFound { span, .. }
+            | ValidationError::IndirectDependency { span, .. }
+            | ValidationError::DependencyShadowed { span, .. }
+            | ValidationError::SelfImport { span, .. }
+            | ValidationError::UnusedImport { span, .. }
+            | ValidationError::UnusedImportedType { span, .. }
+            | ValidationError::ImportedTypeNotFound { span, .. }
+            | ValidationError::ModuleNotImported { span, .. }
+            | ValidationError::TypeNotFound { span, .. }
+            | ValidationError::InterfaceAsType { span, .. }
+            | ValidationError::InvalidKeyType { span, .. }
+            | ValidationError::InvalidArraySize { span, .. }
+            | ValidationError::InvalidDefault { span, .. }
+            | ValidationError::MissingDefault { span, .. }
+            | ValidationError::RecursiveSchema { span, .. }
+            | ValidationError::UnknownAnnotation { span, .. }
+            | ValidationError::InvalidAnnotation { span, .. }
+            | ValidationError::ReservedVariant { span, .. }
+            | ValidationError::InvalidEnumValue { span, .. } => *span,
+            ValidationError::DuplicateImport { second, .. }
+            | ValidationError::ImportConflict { second, .. }
+            | ValidationError::DuplicateDefinition { second, .. }
+            | ValidationError::DuplicateField { second, .. }
+            | ValidationError::DuplicateVariant { second, .. }
+            | ValidationError::DuplicateFunction { second, .. }
+            | ValidationError::DuplicateEnumValue { second, .. } => *second,
+        }
+    }
+
+    /// How serious this error is.
+    ///
+    /// Only unused imports are warnings, they never make a workspace invalid.
+    pub fn severity(&self) -> Severity {
+        match self {
+            ValidationError::UnusedImport { .. }
+            | ValidationError::UnusedImportedType { .. }
+            | ValidationError::MissingDefault { .. } => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
 }
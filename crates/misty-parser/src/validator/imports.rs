@@ -1,5 +1,6 @@
 use crate::validator::ValidationError;
-use misty_ast::File;
+use crate::validator::scope::ModuleScope;
+use misty_ast::{DataType, Definition, File, Import, Span};
 use std::collections::HashMap;
 
 /// Solves the imports of a module.
@@ -7,17 +8,217 @@ use std::collections::HashMap;
 /// This is a simple algorithm where we check each import in the current module against the entire
 /// workspace modules.
 ///
-/// This function attests that at the very least, the module being imported is in scope.
-pub fn attest_imports(
-    imports: &[String],
-    modules: &HashMap<String, File>,
-) -> Result<(), ValidationError> {
-    for import in imports {
-        if !modules.contains_key(import) {
-            tracing::trace!(?import, "Import not found in workspace");
-            return Err(ValidationError::ImportedModuleNotFound);
+/// This function attests that at the very least, the module being imported is in scope, that it's
+/// not the module itself and that it's imported only once. The types selected by an import must be
+/// declared in its module, and the aliases and selected types must not clash with other names of
+/// the module. Modules of other packages are imported through their package name, like
+/// `common_types.users`, from direct dependencies only. Imports never used by a type reference of
+/// the module are reported too, as warnings.
+/// Every problem found is reported to `errors`.
+pub fn attest_imports(scope: &ModuleScope, errors: &mut Vec<ValidationError>) {
+    let references = type_references(scope);
+    let mut imported = HashMap::<(&str, Option<&str>, bool), Span>::new();
+
+    // Names usable in type references: definitions, module qualifiers and selected types.
+    let mut names = HashMap::<&str, Span>::new();
+    for definition in &scope.file.definitions {
+        names.entry(definition.name()).or_insert(definition.span());
+    }
+
+    for import in &scope.file.imports {
+        let module = scope.module_path.to_string();
+        let key = (
+            import.path.as_str(),
+            import.alias.as_deref(),
+            import.is_selective(),
+        );
+        if import.path == scope.module_path {
+            tracing::trace!(?import.path, "Module importing itself");
+            errors.push(ValidationError::SelfImport {
+                module,
+                span: import.span,
+            });
+        } else if let Some(&first) = imported.get(&key) {
+            tracing::trace!(?import.path, "Module imported more than once");
+            errors.push(ValidationError::DuplicateImport {
+                module,
+                import: import.path.clone(),
+                first,
+                second: import.span,
+            });
+        } else {
+            attest_names(scope, import, &mut names, errors);
+
+            match scope.workspace.module(&import.path) {
+                Some(imported) => attest_usage(scope, import, imported, &references, errors),
+                None => attest_visibility(scope, import, errors),
+            }
+        }
+
+        imported.entry(key).or_insert(import.span);
+    }
+}
+
+/// Helper function to check that the names an import brings in scope are not already used.
+fn attest_names<'a>(
+    scope: &ModuleScope,
+    import: &'a Import,
+    names: &mut HashMap<&'a str, Span>,
+    errors: &mut Vec<ValidationError>,
+) {
+    let qualifier = import.qualifier().map(|qualifier| (qualifier, import.span));
+    let items = import
+        .items
+        .iter()
+        .map(|item| (item.name.as_str(), item.span));
+
+    for (name, span) in qualifier.into_iter().chain(items) {
+        match names.get(name) {
+            Some(&first) => {
+                tracing::trace!(?name, "Imported name already used");
+                errors.push(ValidationError::ImportConflict {
+                    module: scope.module_path.to_string(),
+                    name: name.to_string(),
+                    first,
+                    second: span,
+                });
+            }
+            None => {
+                names.insert(name, span);
+            }
+        }
+    }
+}
+
+/// Helper function to report an import not found in the workspace.
+///
+/// Only the modules of the package and of its direct dependencies can be imported. Modules of the
+/// dependencies of a dependency exist in the workspace, but importing them is reported as such.
+fn attest_visibility(scope: &ModuleScope, import: &Import, errors: &mut Vec<ValidationError>) {
+    let module = scope.module_path.to_string();
+    let package = import.path.split('.').next().unwrap_or_default();
+
+    if scope.workspace.is_indirect_dependency(package) {
+        tracing::trace!(?import.path, "Import of an indirect dependency");
+        errors.push(ValidationError::IndirectDependency {
+            module,
+            import: import.path.clone(),
+            package: package.to_string(),
+            span: import.span,
+        });
+    } else {
+        tracing::trace!(?import.path, "Import not found in workspace");
+        errors.push(ValidationError::ImportedModuleNotFound {
+            module,
+            import: import.path.clone(),
+            span: import.span,
+        });
+    }
+}
+
+/// Helper function to check that the types selected by an import exist, and that the import is
+/// used by the type references of the module.
+fn attest_usage(
+    scope: &ModuleScope,
+    import: &Import,
+    imported: &File,
+    references: &[&str],
+    errors: &mut Vec<ValidationError>,
+) {
+    let module = scope.module_path;
+    let uses = |type_name: &&str| {
+        scope
+            .file
+            .import_of(type_name)
+            .is_some_and(|found| std::ptr::eq(found, import))
+    };
+
+    if !import.is_selective() {
+        if !references.iter().any(uses) {
+            tracing::trace!(?import.path, "Import never used");
+            errors.push(ValidationError::UnusedImport {
+                module: module.to_string(),
+                import: import.path.clone(),
+                span: import.span,
+            });
+        }
+        return;
+    }
+
+    // Selected types must be declared in the imported module. An import whose selected types are
+    // all unused is reported as a whole.
+    let declared = &imported.definitions;
+    let mut unused = Vec::new();
+    for item in &import.items {
+        if !declared
+            .iter()
+            .any(|definition| definition.name() == item.name)
+        {
+            tracing::trace!(?import.path, ?item.name, "Imported type not found");
+            errors.push(ValidationError::ImportedTypeNotFound {
+                module: module.to_string(),
+                import: import.path.clone(),
+                name: item.name.clone(),
+                span: item.span,
+            });
+        } else if !references
+            .iter()
+            .filter(|type_name| **type_name == item.name)
+            .any(uses)
+        {
+            unused.push(item);
+        }
+    }
+
+    if unused.len() == import.items.len() {
+        tracing::trace!(?import.path, "Import never used");
+        errors.push(ValidationError::UnusedImport {
+            module: module.to_string(),
+            import: import.path.clone(),
+            span: import.span,
+        });
+    } else {
+        for item in unused {
+            tracing::trace!(?import.path, ?item.name, "Imported type never used");
+            errors.push(ValidationError::UnusedImportedType {
+                module: module.to_string(),
+                import: import.path.clone(),
+                name: item.name.clone(),
+                span: item.span,
+            });
+        }
+    }
+}
+
+/// Collects the user types referenced by a module, as written in the source.
+fn type_references<'a>(scope: &ModuleScope<'a>) -> Vec<&'a str> {
+    let mut data_types = Vec::new();
+    for definition in &scope.file.definitions {
+        match definition {
+            Definition::Enum(_) => (),
+            Definition::Schema(schema) => {
+                data_types.extend(schema.fields.iter().map(|field| &field.field_type));
+            }
+            Definition::Union(union) => {
+                data_types.extend(union.variants.iter().map(|variant| &variant.variant_type));
+            }
+            Definition::Interface(interface) => {
+                for function in &interface.functions {
+                    data_types.push(&function.input.1);
+                    data_types.extend(function.output.iter().map(|(_, output)| output));
+                }
+            }
+        }
+    }
+
+    let mut references = Vec::new();
+    while let Some(data_type) = data_types.pop() {
+        match data_type {
+            DataType::Primitive(_) => (),
+            DataType::UserType(type_name) => references.push(type_name.as_str()),
+            DataType::Container(_, inner) => data_types.extend(inner),
         }
     }
 
-    Ok(())
+    references
 }
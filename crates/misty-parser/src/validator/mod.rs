@@ -3,14 +3,26 @@
 //! Validating starts by adding all detected modules, either `package-local` or `package-remote`
 //! into the workspace. Then, we check each file if their imports are to be found in the modules'
 //! registry. Afterward, all definitions are validated by resolving their `module-local`,
-//! `package-local`, and `package-remote` user-defined types.
+//! `package-local`, and `package-remote` user-defined types, the default values of fields are
+//! type-checked and the values of enum variants are checked. Annotations are checked too, except
+//! the ones passed through to code generators.
 //!
-//! `package-remote` is currently not implemented, it is planned for implementation with the
-//! package manager and registry feature.
+//! Finally, the whole workspace is checked for schemas and unions containing themselves by value.
+//!
+//! `package-remote` modules are the local modules of the dependencies of the package, each one
+//! validated as a workspace of its own before the package depending on it.
+mod annotations;
+mod cycles;
+mod defaults;
+mod duplicates;
+mod enums;
 mod error;
 mod imports;
+mod package;
+mod scope;
 mod type_resolver;
 mod workspace;
 
-pub use error::ValidationError;
+pub use error::{Severity, ValidationError, ValidationErrorKind};
+pub use package::Package;
 pub use workspace::Workspace;
@@ -0,0 +1,21 @@
+use semver::Version;
+use std::fmt;
+
+/// A Misty package, owning the `package-local` modules of a workspace.
+///
+/// Packages are declared by the manifest of a project, this is only what the validator and the
+/// code generators need to know about them.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Package {
+    /// Name of the package, in snake_case.
+    pub name: String,
+
+    /// Version of the package.
+    pub version: Version,
+}
+
+impl fmt::Display for Package {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.name, self.version)
+    }
+}
@@ -0,0 +1,41 @@
+use crate::Workspace;
+use misty_ast::{Definition, File};
+
+/// The module being validated, along with everything visible from it.
+///
+/// Validation passes take a scope instead of passing the workspace modules, module path and file
+/// around separately.
+pub struct ModuleScope<'a> {
+    /// The workspace of the module, giving access to every module visible from it.
+    pub workspace: &'a Workspace,
+
+    /// Path of the module being validated.
+    pub module_path: &'a str,
+
+    /// The module being validated.
+    pub file: &'a File,
+}
+
+impl<'a> ModuleScope<'a> {
+    /// Splits a type reference into the path of the module declaring it and the type name.
+    ///
+    /// Module-local types are declared in the module of this scope, aliases and selective imports
+    /// are followed. The reference is not resolved, the returned module may not exist or not
+    /// declare the type.
+    pub fn qualify(&self, type_name: &'a str) -> (&'a str, &'a str) {
+        self.file.qualify(self.module_path, type_name)
+    }
+
+    /// Finds the definition a type reference points to, if any.
+    ///
+    /// Unlike the type resolver, this does not report anything when the reference does not
+    /// resolve, and does not check that the module declaring the type is imported.
+    pub fn find_definition(&self, type_name: &'a str) -> Option<&'a Definition> {
+        let (module_path, name) = self.qualify(type_name);
+        self.workspace
+            .module(module_path)?
+            .definitions
+            .iter()
+            .find(|definition| definition.name() == name)
+    }
+}
@@ -1,92 +1,185 @@
 use crate::validator::ValidationError;
-use misty_ast::{DataType, Definition, File};
-use std::collections::HashMap;
+use crate::validator::scope::ModuleScope;
+use misty_ast::{ContainerType, DataType, Definition, File, Span};
 
 /// Resolve a [DataType] against the workspace.
 ///
 /// This function will check both containers and user-defined types by executing a module-local
 /// lookup first, then a workspace lookup.
 ///
-/// Primitives are not resolved because they are guaranteed to be present in the workspace.
+/// Primitives are not resolved because they are guaranteed to be present in the workspace. User
+/// types must resolve to a schema, an enum or a union: interfaces are not data types.
 ///
-/// Failing fast at any first resolution failure it founds.
+/// Every resolution failure is reported to `errors`, including each unresolved type nested inside
+/// containers. The `definition` and `span` are the name of the definition and the location of the
+/// node declaring the [DataType], used to report errors.
 pub fn resolve_data_type(
-    modules: &HashMap<String, File>,
-    file: &File,
+    scope: &ModuleScope,
+    definition: &str,
     data_type: &DataType,
-) -> Result<(), ValidationError> {
+    span: Span,
+    errors: &mut Vec<ValidationError>,
+) {
     match data_type {
         // Skipped since they are always valid.
         DataType::Primitive(_) => (),
         // We treat this recursively since containers can contain other containers.
-        DataType::Container(_, inner) => resolve_data_type(modules, file, inner)?,
+        DataType::Container(container_type, inner) => {
+            match container_type {
+                ContainerType::Map | ContainerType::Set => {
+                    attest_key_type(scope, definition, &inner[0], span, errors)
+                }
+                ContainerType::Array(size) => {
+                    attest_array_size(scope, definition, data_type, *size, span, errors)
+                }
+                _ => (),
+            }
+
+            for data_type in inner {
+                resolve_data_type(scope, definition, data_type, span, errors);
+            }
+        }
         // Look up the type in the both the local and workspace modules.
-        DataType::UserType(type_name) => lookup_type(modules, file, type_name)?,
+        DataType::UserType(type_name) => lookup_type(scope, definition, type_name, span, errors),
+    }
+}
+
+/// Helper method to check that the key type of a map, or the element type of a set, is a primitive
+/// or an enum.
+///
+/// Floats are rejected too, since they can neither be hashed nor totally ordered. Keys that do not
+/// resolve are skipped, they are reported by the type resolution itself.
+fn attest_key_type(
+    scope: &ModuleScope,
+    definition: &str,
+    key: &DataType,
+    span: Span,
+    errors: &mut Vec<ValidationError>,
+) {
+    let valid = match key {
+        DataType::Primitive(primitive) => !matches!(primitive.as_str(), "f32" | "f64"),
+        DataType::UserType(type_name) => !matches!(
+            scope.find_definition(type_name),
+            Some(Definition::Schema(_) | Definition::Union(_) | Definition::Interface(_))
+        ),
+        DataType::Container(..) => false,
+    };
+
+    if !valid {
+        tracing::debug!(%key, "Invalid key type");
+        errors.push(ValidationError::InvalidKeyType {
+            module: scope.module_path.to_string(),
+            definition: definition.to_string(),
+            type_name: key.to_string(),
+            span,
+        });
     }
+}
 
-    Ok(())
+/// Helper method to check that a fixed-size array holds from 1 up to 32 elements, the sizes serde
+/// can (de)serialize natively.
+fn attest_array_size(
+    scope: &ModuleScope,
+    definition: &str,
+    data_type: &DataType,
+    size: usize,
+    span: Span,
+    errors: &mut Vec<ValidationError>,
+) {
+    if !(1..=32).contains(&size) {
+        tracing::debug!(%data_type, "Invalid array size");
+        errors.push(ValidationError::InvalidArraySize {
+            module: scope.module_path.to_string(),
+            definition: definition.to_string(),
+            type_name: data_type.to_string(),
+            span,
+        });
+    }
 }
 
 /// Helper method to look up a type in the workspace and the current module file.
 fn lookup_type(
-    modules: &HashMap<String, File>,
-    file: &File,
+    scope: &ModuleScope,
+    definition: &str,
     type_name: &str,
-) -> Result<(), ValidationError> {
+    span: Span,
+    errors: &mut Vec<ValidationError>,
+) {
     // If the type name contains a dot, this means this is either a `package-local` or
-    // `package-remote` type.
+    // `package-remote` type, qualified by its module path or the alias of its module.
     //
-    // Otherwise, it's a `package-local` type, and we resolve it against the same file.
-    if type_name.contains('.') {
-        // The last part of the name is the actual type name, the rest is the module path.
-        let (import_path, type_name) = match type_name.rsplit_once('.') {
-            Some(parts) => parts,
-            None => {
-                tracing::debug!(?type_name, "Invalid type name");
-                return Err(ValidationError::ModuleImportPath);
-            }
-        };
-
-        // Check if the file is importing the import path of the module.
-        if !file.imports.contains(&import_path.to_string()) {
-            tracing::debug!(?import_path, ?type_name, "Module import not found");
-            return Err(ValidationError::ModuleNotImported);
-        }
+    // Otherwise, it's either a type selected by an import, or a `module-local` type, and we
+    // resolve it against the same file.
+    let import = scope.file.import_of(type_name);
+    if let (None, Some((import_path, short_name))) = (import, type_name.rsplit_once('.')) {
+        // The qualifier is neither the path nor the alias of an imported module.
+        tracing::debug!(?import_path, ?short_name, "Module import not found");
+        errors.push(ValidationError::ModuleNotImported {
+            module: scope.module_path.to_string(),
+            definition: definition.to_string(),
+            import: import_path.to_string(),
+            type_name: type_name.to_string(),
+            span,
+        });
+        return;
+    }
 
-        // Get the file from the workspace which, in theory, contains the required type.
-        //
-        // We handle the failing use case instead of unwrapping, even though unwrapping here would
-        // actually be safe since we validate all imports beforehand.
-        let type_module_file = match modules.get(import_path) {
+    // Get the file from the workspace which, in theory, contains the required type.
+    //
+    // A missing module was already reported when attesting the imports of the file, so it's not
+    // reported again for every type referencing it.
+    let (import_path, short_name) = scope.qualify(type_name);
+    let type_module_file = match import {
+        Some(_) => match scope.workspace.module(import_path) {
             Some(file) => file,
             None => {
-                tracing::debug!(?import_path, ?type_name, "Module not found in workspace");
-                return Err(ValidationError::ImportedModuleNotFound);
+                tracing::debug!(?import_path, ?short_name, "Module not found in workspace");
+                return;
             }
-        };
+        },
+        None => scope.file,
+    };
 
-        // Check if the type actually exists in the target module file.
-        if !file_has_type(type_module_file, type_name) {
-            tracing::debug!(?import_path, ?type_name, "Type not found in module");
-            return Err(ValidationError::TypeNotFound);
-        }
+    // Check if the type actually exists in the target module file.
+    let found = find_definition(type_module_file, short_name);
+    attest_data_type(scope, definition, type_name, found, span, errors);
+}
 
-        Ok(())
-    } else {
-        if !file_has_type(file, type_name) {
-            tracing::debug!(?type_name, "Type not found in file");
-            return Err(ValidationError::TypeNotFound);
+/// Helper method to check that a type reference resolved to a definition usable as a data type.
+fn attest_data_type(
+    scope: &ModuleScope,
+    definition: &str,
+    type_name: &str,
+    found: Option<&Definition>,
+    span: Span,
+    errors: &mut Vec<ValidationError>,
+) {
+    match found {
+        Some(Definition::Schema(_) | Definition::Enum(_) | Definition::Union(_)) => (),
+        Some(Definition::Interface(_)) => {
+            tracing::debug!(?type_name, "Interface used as a data type");
+            errors.push(ValidationError::InterfaceAsType {
+                module: scope.module_path.to_string(),
+                definition: definition.to_string(),
+                type_name: type_name.to_string(),
+                span,
+            });
+        }
+        None => {
+            tracing::debug!(?type_name, "Type not found");
+            errors.push(ValidationError::TypeNotFound {
+                module: scope.module_path.to_string(),
+                definition: definition.to_string(),
+                type_name: type_name.to_string(),
+                span,
+            });
         }
-
-        Ok(())
     }
 }
 
-/// Helper method to check if a file contains a definition with a given type name.
-fn file_has_type(file: &File, type_name: &str) -> bool {
-    file.definitions.iter().any(|definition| match definition {
-        Definition::Interface(interface) => interface.name.eq(type_name),
-        Definition::Schema(schema) => schema.name.eq(type_name),
-        Definition::Enum(misty_enum) => misty_enum.name.eq(type_name),
-    })
+/// Helper method to find the definition declaring a given type name in a file.
+fn find_definition<'a>(file: &'a File, type_name: &str) -> Option<&'a Definition> {
+    file.definitions
+        .iter()
+        .find(|definition| definition.name() == type_name)
 }
@@ -1,23 +1,62 @@
 //! The Workspace module contains the logic to build a Misty workspace for validation purposes.
 //!
-//! The validation method uses a fail-fast strategy instead of returning all cumulative errors, this
-//! is simply to reduce complexity. It's not a hard requirement and can be improved later.
+//! Two validation modes are available: [Workspace::validate] stops at the first module with an
+//! error and returns its first error, while [Workspace::validate_all] walks every module and
+//! returns every error found, so large schema sets can be fixed in a single pass.
 //!
-//! As `package-remote` modules are not yet here, we only validate against `package-local` modules.
-use crate::ParserError;
-use crate::validator::ValidationError;
+//! Warnings, like unused imports, never fail a validation. They are kept apart in
+//! [Workspace::warnings] instead, for tools to report them.
+//!
+//! Modules are either added one by one with [Workspace::add_local_module], or loaded from the
+//! Misty files of a directory with [Workspace::load_dir].
+//!
+//! `package-remote` modules come from the workspaces of the dependencies of the package, added with
+//! [Workspace::add_dependency]. They are imported through the name of their dependency, like
+//! `common_types.users`, and only the modules of direct dependencies are visible.
+use crate::loader::{LoadError, SourceFile, find_misty_files, module_path};
+use crate::validator::annotations::check_annotations;
+use crate::validator::cycles::{check_cycles, recursive_references};
+use crate::validator::defaults::attest_default;
+use crate::validator::duplicates::check_duplicates;
+use crate::validator::enums::check_enum_values;
 use crate::validator::imports::attest_imports;
+use crate::validator::scope::ModuleScope;
 use crate::validator::type_resolver::resolve_data_type;
+use crate::validator::{Package, Severity, ValidationError};
+use crate::{ParserError, parse};
 use misty_ast::{Definition, File};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
 
 /// The project workspace.
 ///
 /// It contains all modules imported and validates them against each other.
 pub struct Workspace {
+    /// Package owning the local modules, if the workspace was loaded from a project manifest.
+    package: Option<Package>,
+
     /// Modules found in the current workspace.
     package_local_modules: HashMap<String, File>,
 
+    /// Workspaces of the packages the local modules can import from, by dependency name.
+    dependencies: HashMap<String, Workspace>,
+
+    /// Files the local modules were loaded from, if they were loaded from a directory.
+    source_files: HashMap<String, SourceFile>,
+
+    /// References that must be stored behind an indirection to break a cycle.
+    ///
+    /// Each one is the module path and name of the referencing schema, along with the reference as
+    /// written in the source. Only computed once the workspace is validated.
+    recursive_references: HashSet<(String, String, String)>,
+
+    /// Namespaces of the annotations passed through to code generators, like `rust`.
+    annotation_namespaces: HashSet<String>,
+
+    /// Warnings found by the last validation, sorted like errors.
+    warnings: Vec<ValidationError>,
+
     /// Flag that marks this Workspace as validated.
     validated: bool,
 }
@@ -32,7 +71,13 @@ impl Workspace {
     /// Creates a new, empty workspace.
     pub fn new() -> Self {
         Self {
+            package: None,
             package_local_modules: HashMap::new(),
+            dependencies: HashMap::new(),
+            source_files: HashMap::new(),
+            recursive_references: HashSet::new(),
+            annotation_namespaces: HashSet::new(),
+            warnings: Vec::new(),
             validated: false,
         }
     }
@@ -43,53 +88,320 @@ impl Workspace {
             .insert(module_path.to_string(), module);
     }
 
+    /// Sets the package owning the local modules of the workspace.
+    pub fn set_package(&mut self, package: Package) {
+        self.package = Some(package);
+    }
+
+    /// Returns the package owning the local modules of the workspace, if any.
+    pub fn package(&self) -> Option<&Package> {
+        self.package.as_ref()
+    }
+
+    /// Adds the workspace of a dependency, whose local modules are imported through `name`.
+    ///
+    /// The dependency is validated along with this workspace, unless it was validated already, and
+    /// accepts the annotations of the namespaces registered in this workspace.
+    pub fn add_dependency(&mut self, name: &str, mut dependency: Workspace) {
+        for namespace in &self.annotation_namespaces {
+            dependency.add_annotation_namespace(namespace);
+        }
+        self.dependencies.insert(name.to_string(), dependency);
+    }
+
+    /// Gets the workspaces of the dependencies, by dependency name.
+    pub fn dependencies(&self) -> &HashMap<String, Workspace> {
+        &self.dependencies
+    }
+
+    /// Finds a module visible from the local modules.
+    ///
+    /// This is either a local module, or a local module of a direct dependency qualified by the
+    /// name of the dependency, like `common_types.users`.
+    pub fn module(&self, module_path: &str) -> Option<&File> {
+        if let Some(file) = self.package_local_modules.get(module_path) {
+            return Some(file);
+        }
+
+        let (dependency, module_path) = module_path.split_once('.')?;
+        self.dependencies
+            .get(dependency)?
+            .package_local_modules
+            .get(module_path)
+    }
+
+    /// Checks if a package is a dependency of a dependency, but not a direct dependency.
+    pub fn is_indirect_dependency(&self, name: &str) -> bool {
+        !self.dependencies.contains_key(name) && self.has_dependency(name)
+    }
+
+    /// Helper method to check if a package is a dependency, direct or not.
+    fn has_dependency(&self, name: &str) -> bool {
+        self.dependencies
+            .iter()
+            .any(|(dependency, workspace)| dependency == name || workspace.has_dependency(name))
+    }
+
+    /// Loads every Misty file of a directory and its subdirectories as a local module.
+    ///
+    /// Module paths are derived from the paths of the files relative to `root`:
+    /// `ml/analyzer.misty` declares `ml.analyzer`. The file of every module is recorded, see
+    /// [Workspace::source_file]. Every file is loaded even if some fail, and every error found is
+    /// returned.
+    #[tracing::instrument]
+    pub fn load_dir(root: &Path) -> Result<Self, Vec<LoadError>> {
+        let mut workspace = Self::new();
+        workspace.add_dir(root)?;
+        Ok(workspace)
+    }
+
+    /// Loads every Misty file of a directory and its subdirectories as a local module of this
+    /// workspace, see [Workspace::load_dir].
+    ///
+    /// This is used to load packages with several source roots. A module already loaded from
+    /// another directory is reported as a [LoadError::DuplicateModule].
+    #[tracing::instrument(skip(self))]
+    pub fn add_dir(&mut self, root: &Path) -> Result<(), Vec<LoadError>> {
+        let mut errors = Vec::new();
+
+        for path in find_misty_files(root).map_err(|error| vec![error])? {
+            let module_path = match module_path(root, &path) {
+                Ok(module_path) => module_path,
+                Err(error) => {
+                    errors.push(error);
+                    continue;
+                }
+            };
+            if let Some(first) = self.source_files.get(&module_path) {
+                errors.push(LoadError::DuplicateModule {
+                    module: module_path,
+                    first: first.path.clone(),
+                    second: path,
+                });
+                continue;
+            }
+            let source = match fs::read_to_string(&path) {
+                Ok(source) => source,
+                Err(source) => {
+                    errors.push(LoadError::Read { path, source });
+                    continue;
+                }
+            };
+
+            let file = SourceFile { path, source };
+            match parse(&file.source) {
+                Ok(module) => {
+                    self.add_local_module(&module_path, module);
+                    self.source_files.insert(module_path, file);
+                }
+                Err(error) => {
+                    tracing::debug!(?file.path, ?error, "Failed to parse a module");
+                    errors.push(LoadError::Parse {
+                        file,
+                        error: Box::new(error),
+                    });
+                }
+            }
+        }
+
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        Ok(())
+    }
+
+    /// Registers the namespace of annotations handled by a code generator, like `rust` for
+    /// `@rust(derive = "PartialEq")`.
+    ///
+    /// Annotations of registered namespaces are passed through as-is by the validator, while
+    /// unknown annotations are rejected. Namespaces are registered in the dependencies too.
+    pub fn add_annotation_namespace(&mut self, namespace: &str) {
+        self.annotation_namespaces.insert(namespace.to_string());
+        for dependency in self.dependencies.values_mut() {
+            dependency.add_annotation_namespace(namespace);
+        }
+    }
+
     /// Validates all modules in the workspace.
     ///
     /// This function is fail-fast. Meaning, it will fail at each first error it founds.
     #[tracing::instrument(skip(self))]
     pub fn validate(&mut self) -> Result<(), ParserError> {
-        for file in self.package_local_modules.values() {
-            self.validate_file(file)?;
+        // Warnings of a previous validation are stale, even if this one fails.
+        self.warnings.clear();
+
+        if let Err(errors) = self.validate_dependencies()
+            && let Some(error) = errors.into_iter().next()
+        {
+            return Err(Box::new(error).into());
         }
 
-        self.validated = true;
+        let mut warnings = Vec::new();
+        for (module_path, file) in &self.package_local_modules {
+            let mut errors = Vec::new();
+            self.validate_file(module_path, file, &mut errors);
+
+            let (file_errors, file_warnings) = split_warnings(errors);
+            if let Some(error) = file_errors.into_iter().next() {
+                return Err(Box::new(error).into());
+            }
+            warnings.extend(file_warnings);
+        }
+
+        let mut errors = Vec::new();
+        check_cycles(self, &mut errors);
+        if let Some(error) = errors.into_iter().next() {
+            return Err(Box::new(error).into());
+        }
+
+        self.mark_validated(warnings);
         Ok(())
     }
 
-    /// Validates a single file.
+    /// Validates all modules in the workspace, accumulating every error found.
+    ///
+    /// Errors are sorted by module path, then by their position in the module. Warnings are never
+    /// returned, see [Workspace::warnings].
     ///
-    /// This function is fail-fast. It will return the first error it finds as soon as it does.
-    #[tracing::instrument(skip(self, file))]
-    fn validate_file(&self, file: &File) -> Result<(), ValidationError> {
+    /// Dependencies are validated first, and their errors returned instead when they are invalid.
+    /// Their modules are qualified by the name of their dependency, see [Workspace::source_file].
+    #[tracing::instrument(skip(self))]
+    pub fn validate_all(&mut self) -> Result<(), Vec<ValidationError>> {
+        self.warnings.clear();
+
+        self.validate_dependencies()?;
+
+        let mut errors = Vec::new();
+        for (module_path, file) in &self.package_local_modules {
+            self.validate_file(module_path, file, &mut errors);
+        }
+        check_cycles(self, &mut errors);
+        errors.sort_by(|a, b| (a.module(), a.span().start).cmp(&(b.module(), b.span().start)));
+
+        let (errors, warnings) = split_warnings(errors);
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        self.mark_validated(warnings);
+        Ok(())
+    }
+
+    /// Validates the dependencies not validated yet, returning the errors of the invalid ones.
+    fn validate_dependencies(&mut self) -> Result<(), Vec<ValidationError>> {
+        let mut dependencies = self.dependencies.iter_mut().collect::<Vec<_>>();
+        dependencies.sort_by_key(|(name, _)| name.as_str());
+
+        let mut errors = Vec::new();
+        for (name, dependency) in dependencies {
+            if dependency.validated {
+                continue;
+            }
+            if let Err(dependency_errors) = dependency.validate_all() {
+                tracing::debug!(?name, "Invalid dependency");
+                errors.extend(
+                    dependency_errors
+                        .into_iter()
+                        .map(|error| error.in_dependency(name)),
+                );
+            }
+        }
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        Ok(())
+    }
+
+    /// Marks the workspace as validated, computing everything that requires a valid workspace.
+    fn mark_validated(&mut self, mut warnings: Vec<ValidationError>) {
+        warnings.sort_by(|a, b| (a.module(), a.span().start).cmp(&(b.module(), b.span().start)));
+        self.warnings = warnings;
+        self.recursive_references = recursive_references(self);
+        self.validated = true;
+    }
+
+    /// Validates a single file, reporting every error it finds to `errors`.
+    #[tracing::instrument(skip(self, file, errors))]
+    fn validate_file(&self, module_path: &str, file: &File, errors: &mut Vec<ValidationError>) {
+        let scope = ModuleScope {
+            workspace: self,
+            module_path,
+            file,
+        };
+
+        // Imports starting with the name of a dependency resolve to the dependency, so the module
+        // could never be imported.
+        let package = module_path.split('.').next().unwrap_or_default();
+        if self.dependencies.contains_key(package) {
+            tracing::trace!(?module_path, ?package, "Module shadowing a dependency");
+            errors.push(ValidationError::DependencyShadowed {
+                module: module_path.to_string(),
+                package: package.to_string(),
+                span: file.span,
+            });
+        }
+
         // Run a fast check in the imports of the file to attest that at the very least, the module
         // is in scope.
-        attest_imports(&file.imports, &self.package_local_modules)?;
+        attest_imports(&scope, errors);
+
+        // Check that every definition and member is declared only once.
+        check_duplicates(&scope, errors);
+
+        // Check that enum values can be encoded and told apart on the wire.
+        check_enum_values(&scope, errors);
+
+        // Check the built-in annotations, and that every other annotation is passed through.
+        check_annotations(&scope, &self.annotation_namespaces, errors);
 
         // Validate all the definitions in the file.
         for definition in &file.definitions {
             match definition {
-                // Enums do not reference a type, so they are skipped from validation.
+                // Enums do not reference a type, their values were checked above.
                 Definition::Enum(_) => (),
                 Definition::Interface(interface) => {
+                    let name = &interface.name;
                     for function in &interface.functions {
                         // Resolve the input argument type.
-                        resolve_data_type(&self.package_local_modules, file, &function.input.1)?;
+                        let input = &function.input.1;
+                        resolve_data_type(&scope, name, input, function.span, errors);
 
                         // Resolve the output argument type.
-                        if let Some((_, argument)) = &function.output {
-                            resolve_data_type(&self.package_local_modules, file, argument)?;
+                        if let Some((_, output)) = &function.output {
+                            resolve_data_type(&scope, name, output, function.span, errors);
                         }
                     }
                 }
                 Definition::Schema(schema) => {
                     for field in &schema.fields {
-                        resolve_data_type(&self.package_local_modules, file, &field.field_type)?;
+                        let field_type = &field.field_type;
+                        resolve_data_type(&scope, &schema.name, field_type, field.span, errors);
+                        attest_default(&scope, &schema.name, field, errors);
+                    }
+                }
+                Definition::Union(union) => {
+                    for variant in &union.variants {
+                        let variant_type = &variant.variant_type;
+                        resolve_data_type(&scope, &union.name, variant_type, variant.span, errors);
                     }
                 }
             }
         }
+    }
 
-        Ok(())
+    /// Gets the file a local module was loaded from, if it was loaded with [Workspace::load_dir].
+    ///
+    /// Modules of dependencies are found too, qualified by the name of their dependency like in
+    /// the errors of [Workspace::validate_all].
+    pub fn source_file(&self, module_path: &str) -> Option<&SourceFile> {
+        if let Some(file) = self.source_files.get(module_path) {
+            return Some(file);
+        }
+
+        let (dependency, module_path) = module_path.split_once('.')?;
+        self.dependencies.get(dependency)?.source_file(module_path)
     }
 
     /// Gets a reference to the local modules in the workspace.
@@ -97,8 +409,542 @@ impl Workspace {
         &self.package_local_modules
     }
 
+    /// Checks if a reference must be stored behind an indirection, like a `Box`.
+    ///
+    /// This is the case for references wrapped in an `option` that lead back to the schema or union
+    /// declaring them, which would otherwise have an infinite size. The reference is given as
+    /// written in a field or variant of the definition `schema` from the module `module_path`.
+    ///
+    /// Always `false` until the workspace is validated.
+    pub fn is_recursive_reference(&self, module_path: &str, schema: &str, type_name: &str) -> bool {
+        self.recursive_references.contains(&(
+            module_path.to_string(),
+            schema.to_string(),
+            type_name.to_string(),
+        ))
+    }
+
+    /// Gets the warnings found when the workspace was validated.
+    ///
+    /// Always empty until the workspace is validated, and after a failed validation.
+    pub fn warnings(&self) -> &[ValidationError] {
+        &self.warnings
+    }
+
     /// Checks if the workspace has been validated.
     pub fn validated(&self) -> bool {
         self.validated
     }
 }
+
+/// Helper function to split validation results into errors and warnings.
+fn split_warnings(results: Vec<ValidationError>) -> (Vec<ValidationError>, Vec<ValidationError>) {
+    results
+        .into_iter()
+        .partition(|result| result.severity() == Severity::Error)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        LoadError, MANIFEST_FILE, ParserError, Severity, ValidationErrorKind, Workspace, parse,
+    };
+    use std::fs;
+
+    #[test]
+    fn it_accumulates_errors() {
+        let users = "schema User { address: Address; tags: vec<option<Tag>>; }";
+        let orders = "import missing;\nschema Order { user: users.User; }";
+
+        let mut workspace = Workspace::new();
+        workspace.add_local_module("users", parse(users).unwrap());
+        workspace.add_local_module("orders", parse(orders).unwrap());
+
+        let errors = workspace.validate_all().unwrap_err();
+        let summary = errors
+            .iter()
+            .map(|error| {
+                (
+                    error.module(),
+                    error.definition(),
+                    error.type_name(),
+                    error.kind(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    "orders",
+                    None,
+                    None,
+                    ValidationErrorKind::ImportedModuleNotFound
+                ),
+                (
+                    "orders",
+                    Some("Order"),
+                    Some("users.User"),
+                    ValidationErrorKind::ModuleNotImported
+                ),
+                (
+                    "users",
+                    Some("User"),
+                    Some("Address"),
+                    ValidationErrorKind::TypeNotFound
+                ),
+                (
+                    "users",
+                    Some("User"),
+                    Some("Tag"),
+                    ValidationErrorKind::TypeNotFound
+                ),
+            ]
+        );
+        assert!(!workspace.validated());
+    }
+
+    #[test]
+    fn it_rejects_duplicates() {
+        let source = "schema User { name: str; name: str; }\n\
+                      schema User {}\n\
+                      enum Status { ACTIVE, ACTIVE }\n\
+                      interface Users { fn get(User): User; fn get(User); }";
+
+        let mut workspace = Workspace::new();
+        workspace.add_local_module("users", parse(source).unwrap());
+
+        let errors = workspace.validate_all().unwrap_err();
+        let kinds = errors.iter().map(|error| error.kind()).collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            vec![
+                ValidationErrorKind::DuplicateField,
+                ValidationErrorKind::DuplicateDefinition,
+                ValidationErrorKind::DuplicateVariant,
+                ValidationErrorKind::DuplicateFunction,
+            ]
+        );
+        assert!(
+            errors[1]
+                .to_string()
+                .starts_with("The definition `User` is declared at 1:1 and again at 2:1")
+        );
+    }
+
+    #[test]
+    fn it_rejects_interfaces_as_data_types() {
+        let source = "schema User { service: Users; }\n\
+                      interface Users { fn get(User): Users; }";
+
+        let mut workspace = Workspace::new();
+        workspace.add_local_module("users", parse(source).unwrap());
+
+        let errors = workspace.validate_all().unwrap_err();
+        let summary = errors
+            .iter()
+            .map(|error| (error.definition(), error.kind()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            vec![
+                (Some("User"), ValidationErrorKind::InterfaceAsType),
+                (Some("Users"), ValidationErrorKind::InterfaceAsType),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_rejects_recursive_schemas() {
+        let nodes = "schema Node { next: Node; }\n\
+                     schema Tree { children: vec<Tree>; parent: option<Tree>; }";
+        let graph = "import graph.edges;\n\
+                     schema Vertex { edge: graph.edges.Edge; }";
+        let edges = "import graph;\n\
+                     schema Edge { to: option<graph.Vertex>; from: graph.Vertex; }";
+
+        let mut workspace = Workspace::new();
+        workspace.add_local_module("nodes", parse(nodes).unwrap());
+        workspace.add_local_module("graph", parse(graph).unwrap());
+        workspace.add_local_module("graph.edges", parse(edges).unwrap());
+
+        let errors = workspace.validate_all().unwrap_err();
+        let messages = errors
+            .iter()
+            .map(|error| error.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            messages,
+            vec![
+                "The schema `Vertex` contains itself through \
+                 `graph.Vertex -> graph.edges.Edge -> graph.Vertex` (at graph:2:17)",
+                "The schema `Node` contains itself through `nodes.Node -> nodes.Node` \
+                 (at nodes:1:15)",
+            ]
+        );
+    }
+
+    #[test]
+    fn it_validates_enum_values() {
+        let source = "enum Status { ACTIVE = 1, INACTIVE, DELETED = 2, HUGE = 65535, OVERFLOW }";
+
+        let mut workspace = Workspace::new();
+        workspace.add_local_module("status", parse(source).unwrap());
+
+        let errors = workspace.validate_all().unwrap_err();
+        let messages = errors
+            .iter()
+            .map(|error| error.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            messages,
+            vec![
+                "The value 2 of `Status.DELETED` is used at 1:27 and again at 1:37 (in status)",
+                "The value 65536 of `Status.OVERFLOW` does not fit in a u16 (at status:1:64)",
+            ]
+        );
+    }
+
+    #[test]
+    fn it_validates_default_values() {
+        let source = "schema Config { retries: u8 = 3; ratio: f32 = 1; label: option<str> = none; \
+                      name: option<str> = \"misty\"; overflow: u8 = 256; negative: u32 = -1; \
+                      flag: bool = 1; missing: str = none; tags: vec<str> = none; }";
+
+        let mut workspace = Workspace::new();
+        workspace.add_local_module("config", parse(source).unwrap());
+
+        let errors = workspace.validate_all().unwrap_err();
+        let summary = errors
+            .iter()
+            .map(|error| (error.type_name(), error.kind()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            vec![
+                (Some("u8"), ValidationErrorKind::InvalidDefault),
+                (Some("u32"), ValidationErrorKind::InvalidDefault),
+                (Some("bool"), ValidationErrorKind::InvalidDefault),
+                (Some("str"), ValidationErrorKind::InvalidDefault),
+                (Some("vec<str>"), ValidationErrorKind::InvalidDefault),
+            ]
+        );
+
+        // Literals are parsed as `i128`, which bounds the defaults of `u128` fields.
+        let source = "schema Big { max: u128 = 170141183460469231731687303715884105727; }";
+        assert!(parse(source).is_ok());
+        let source = "schema Big { max: u128 = 170141183460469231731687303715884105728; }";
+        assert!(matches!(parse(source), Err(ParserError::Literal(_))));
+    }
+
+    #[test]
+    fn it_validates_unions() {
+        let source = "schema Card { number: str; }\n\
+                      union Payment { Card: Card; Cash: u64; Card: str; Gift: Coupon; }\n\
+                      union Expr { Value: i64; Not: Expr; Next: option<Expr>; }\n\
+                      schema Order { payment: Payment; }";
+
+        let mut workspace = Workspace::new();
+        workspace.add_local_module("orders", parse(source).unwrap());
+
+        let errors = workspace.validate_all().unwrap_err();
+        let summary = errors
+            .iter()
+            .map(|error| (error.definition(), error.kind()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            vec![
+                (Some("Payment"), ValidationErrorKind::DuplicateVariant),
+                (Some("Payment"), ValidationErrorKind::TypeNotFound),
+                (Some("Expr"), ValidationErrorKind::RecursiveSchema),
+            ]
+        );
+        assert!(
+            errors[2]
+                .to_string()
+                .starts_with("The union `Expr` contains itself")
+        );
+    }
+
+    #[test]
+    fn it_validates_annotations() {
+        let source = "@deprecated(\"use v2\") @rust(derive = \"Hash\") \
+                      schema Old { @deprecated id: u64; }\n\
+                      @idempotent enum Status { @deprecated(1) ACTIVE }\n\
+                      interface Jobs { @idempotent @idempotent fn list(u32); \
+                      @cached fn get(u64); }";
+
+        let mut workspace = Workspace::new();
+        workspace.add_annotation_namespace("rust");
+        workspace.add_local_module("jobs", parse(source).unwrap());
+
+        let errors = workspace.validate_all().unwrap_err();
+        let summary = errors
+            .iter()
+            .map(|error| (error.definition(), error.kind()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            vec![
+                (Some("Status"), ValidationErrorKind::InvalidAnnotation),
+                (Some("Status"), ValidationErrorKind::InvalidAnnotation),
+                (Some("Jobs"), ValidationErrorKind::InvalidAnnotation),
+                (Some("Jobs"), ValidationErrorKind::UnknownAnnotation),
+            ]
+        );
+        assert_eq!(
+            errors[0].to_string(),
+            "The annotation `@idempotent` is invalid, it cannot be used on enums (at jobs:2:1)"
+        );
+    }
+
+    #[test]
+    fn it_finds_recursive_references() {
+        let nodes = "schema Node { value: u32; next: option<Node>; }\n\
+                     schema Tree { children: vec<Tree>; }";
+
+        let mut workspace = Workspace::new();
+        workspace.add_local_module("nodes", parse(nodes).unwrap());
+        workspace.validate().unwrap();
+
+        assert!(workspace.is_recursive_reference("nodes", "Node", "Node"));
+        assert!(!workspace.is_recursive_reference("nodes", "Tree", "Tree"));
+    }
+
+    #[test]
+    fn it_rejects_invalid_containers() {
+        let source = "enum Status { ACTIVE }\n\
+                      schema Key {}\n\
+                      schema Index { by_status: map<Status, str>; by_name: map<str, Key>; \
+                      by_score: map<f32, Key>; by_key: map<Key, vec<Key>>; \
+                      tags: set<str>; keys: set<Key>; hash: array<u8, 32>; \
+                      huge: array<u8, 64>; pair: tuple<str, set<Status>>; }";
+
+        let mut workspace = Workspace::new();
+        workspace.add_local_module("index", parse(source).unwrap());
+
+        let errors = workspace.validate_all().unwrap_err();
+        let summary = errors
+            .iter()
+            .map(|error| (error.type_name(), error.kind()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            vec![
+                (Some("f32"), ValidationErrorKind::InvalidKeyType),
+                (Some("Key"), ValidationErrorKind::InvalidKeyType),
+                (Some("Key"), ValidationErrorKind::InvalidKeyType),
+                (Some("array<u8, 64>"), ValidationErrorKind::InvalidArraySize),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_checks_imports() {
+        let users = "schema User { id: u64; }";
+        let jobs = "import users;\nimport jobs;\nimport auth;\nimport users;\n\
+                    schema Job { owner: option<users.User>; }";
+
+        let mut workspace = Workspace::new();
+        workspace.add_local_module("users", parse(users).unwrap());
+        workspace.add_local_module("auth", parse("").unwrap());
+        workspace.add_local_module("jobs", parse(jobs).unwrap());
+
+        let errors = workspace.validate_all().unwrap_err();
+        let kinds = errors.iter().map(|error| error.kind()).collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            vec![
+                ValidationErrorKind::SelfImport,
+                ValidationErrorKind::DuplicateImport,
+            ]
+        );
+        assert!(
+            errors[1]
+                .to_string()
+                .starts_with("The module `users` is imported at 1:1 and again at 4:1")
+        );
+
+        // Unused imports are warnings, which never fail a validation.
+        let jobs = "import auth;\nimport users;\nschema Job { owner: option<users.User>; }";
+        workspace.add_local_module("jobs", parse(jobs).unwrap());
+        workspace.validate().unwrap();
+
+        let warnings = workspace.warnings();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].kind(), ValidationErrorKind::UnusedImport);
+        assert_eq!(warnings[0].severity(), Severity::Warning);
+        assert_eq!(warnings[0].span().line, 1);
+
+        // Failed validations don't keep the warnings of the previous ones.
+        workspace.add_local_module("broken", parse("schema Broken { x: Missing; }").unwrap());
+        assert!(workspace.validate().is_err());
+        assert!(workspace.warnings().is_empty());
+    }
+
+    #[test]
+    fn it_resolves_aliases_and_selective_imports() {
+        let analyzer = "schema Analysis {} schema Report {} enum Level { LOW }";
+        let jobs = "import ml.analyzer as an;\nimport ml.analyzer.{Report, Level, Missing};\n\
+                    schema Job { analysis: an.Analysis; report: option<Report>; \
+                    full: ml.analyzer.Analysis; }";
+
+        let mut workspace = Workspace::new();
+        workspace.add_local_module("ml.analyzer", parse(analyzer).unwrap());
+        workspace.add_local_module("jobs", parse(jobs).unwrap());
+
+        let errors = workspace.validate_all().unwrap_err();
+        let summary = errors
+            .iter()
+            .map(|error| (error.type_name(), error.kind()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            vec![
+                (None, ValidationErrorKind::ImportedTypeNotFound),
+                (
+                    Some("ml.analyzer.Analysis"),
+                    ValidationErrorKind::ModuleNotImported
+                ),
+            ]
+        );
+
+        // Selected types clash with the definitions of the module.
+        let jobs = "import ml.analyzer as an;\nimport ml.analyzer.{Report, Level};\n\
+                    schema Job { analysis: an.Analysis; report: option<Report>; }\n\
+                    schema Level {}";
+        workspace.add_local_module("jobs", parse(jobs).unwrap());
+        let errors = workspace.validate_all().unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].kind(), ValidationErrorKind::ImportConflict);
+
+        let jobs = "import ml.analyzer as an;\nimport ml.analyzer.{Report, Level};\n\
+                    schema Job { analysis: an.Analysis; report: option<Report>; }";
+        workspace.add_local_module("jobs", parse(jobs).unwrap());
+        workspace.validate().unwrap();
+
+        let warnings = workspace.warnings();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].kind(), ValidationErrorKind::UnusedImportedType);
+        assert!(
+            warnings[0]
+                .to_string()
+                .starts_with("The type `Level` imported from `ml.analyzer` is never used")
+        );
+    }
+
+    #[test]
+    fn it_resolves_dependencies() {
+        let mut ids = Workspace::new();
+        ids.add_local_module("uuid", parse("schema Id {}").unwrap());
+        let mut common_types = Workspace::new();
+        common_types.add_local_module(
+            "users",
+            parse(
+                "import ids.uuid;\nschema User { id: ids.uuid.Id; } schema Broken { a: Missing; }",
+            )
+            .unwrap(),
+        );
+        common_types.add_dependency("ids", ids);
+
+        let jobs = "import common_types.users.{User};\nimport ids.uuid;\n\
+                    schema Job { owner: User; id: ids.uuid.Id; }";
+        let mut workspace = Workspace::new();
+        workspace.add_local_module("jobs", parse(jobs).unwrap());
+        workspace.add_local_module("common_types.extra", parse("schema Extra {}").unwrap());
+        workspace.add_dependency("common_types", common_types);
+
+        // Errors of a dependency are found in its qualified modules.
+        let errors = workspace.validate_all().unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].kind(), ValidationErrorKind::TypeNotFound);
+        assert_eq!(errors[0].module(), "common_types.users");
+
+        let common_types = workspace.dependencies.get_mut("common_types").unwrap();
+        let users = "import ids.uuid;\nschema User { id: ids.uuid.Id; }";
+        common_types.add_local_module("users", parse(users).unwrap());
+
+        // Only direct dependencies are visible, and can't be shadowed by local modules.
+        let errors = workspace.validate_all().unwrap_err();
+        let summary = errors
+            .iter()
+            .map(|error| (error.module(), error.kind()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    "common_types.extra",
+                    ValidationErrorKind::DependencyShadowed
+                ),
+                ("jobs", ValidationErrorKind::IndirectDependency),
+            ]
+        );
+
+        let jobs = "import common_types.users.{User};\nschema Job { owner: User; }";
+        workspace.add_local_module("jobs", parse(jobs).unwrap());
+        workspace.package_local_modules.remove("common_types.extra");
+        workspace.validate_all().unwrap();
+        assert!(workspace.module("common_types.users").is_some());
+        assert!(workspace.module("common_types.ids.uuid").is_none());
+        assert!(workspace.is_indirect_dependency("ids"));
+    }
+
+    #[test]
+    fn it_loads_directories() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("ml")).unwrap();
+        fs::create_dir_all(root.join(".cache")).unwrap();
+        fs::write(root.join("ml/analyzer.misty"), "schema Analysis {}").unwrap();
+        fs::write(
+            root.join("jobs.misty"),
+            "import ml.analyzer;\nschema Job { a: ml.analyzer.Analysis; }",
+        )
+        .unwrap();
+        fs::write(root.join(".cache/stale.misty"), "schema").unwrap();
+        fs::write(root.join("notes.txt"), "schema").unwrap();
+        fs::create_dir_all(root.join("vendor/ids")).unwrap();
+        fs::write(root.join("vendor/ids").join(MANIFEST_FILE), "").unwrap();
+        fs::write(root.join("vendor/ids/uuid.misty"), "schema Id {}").unwrap();
+
+        let mut workspace = Workspace::load_dir(root).unwrap();
+        let mut modules = workspace.package_local_modules().keys().collect::<Vec<_>>();
+        modules.sort();
+        assert_eq!(modules, vec!["jobs", "ml.analyzer"]);
+        let file = workspace.source_file("ml.analyzer").unwrap();
+        assert_eq!(file.path, root.join("ml/analyzer.misty"));
+        assert_eq!(file.source, "schema Analysis {}");
+        workspace.validate().unwrap();
+
+        // Modules can't be declared by two source roots.
+        let errors = workspace.add_dir(root).unwrap_err();
+        assert!(matches!(
+            &errors[..],
+            [LoadError::DuplicateModule { module, .. }, ..] if module == "jobs"
+        ));
+
+        // Symlinks are skipped, even when they loop.
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(root, root.join("ml/parent")).unwrap();
+        let workspace = Workspace::load_dir(root).unwrap();
+        assert_eq!(workspace.package_local_modules().len(), 2);
+
+        // Every file is loaded, and every error returned.
+        fs::create_dir_all(root.join("mL")).unwrap();
+        fs::write(root.join("mL/analyzer.misty"), "").unwrap();
+        fs::write(root.join("broken.misty"), "schema {").unwrap();
+        let Err(errors) = Workspace::load_dir(root) else {
+            panic!("expected loading errors");
+        };
+        assert!(matches!(
+            &errors[..],
+            [
+                LoadError::Parse { file, .. },
+                LoadError::ModulePath { segment, .. },
+            ] if file.path == root.join("broken.misty") && segment == "mL"
+        ));
+    }
+}